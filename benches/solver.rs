@@ -0,0 +1,62 @@
+/// Benchmarks the solver's filter of possible words against checking every word,
+/// on 20,000 random five-letter words after two guesses.
+///
+/// wordlebot is a binary crate, so the engine and solver are compiled into the
+/// benchmark directly.
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+#[allow(dead_code)]
+#[path = "../src/wordle.rs"]
+mod wordle;
+
+#[allow(dead_code)]
+#[path = "../src/solver.rs"]
+mod solver;
+
+use solver::WordIndex;
+use wordle::Wordle;
+
+/// The number of random words to filter.
+const WORDS: usize = 20_000;
+
+/// Returns the random words, the same on every run.
+fn random_words() -> Vec<String> {
+    let mut rng = StdRng::seed_from_u64(5);
+    (0..WORDS)
+        .map(|_| (0..5).map(|_| rng.gen_range('a'..='z')).collect())
+        .collect()
+}
+
+/// Returns a game with two guesses played.
+fn game() -> Wordle {
+    let mut wordle = Wordle::new("hello".into()).unwrap();
+    for guess in ["crane", "lotus"] {
+        wordle.play_turn(guess).unwrap();
+    }
+    wordle
+}
+
+fn filter(c: &mut Criterion) {
+    let words = random_words();
+    let index = WordIndex::new(words.iter().cloned());
+    let wordle = game();
+
+    c.bench_function("filter every word", |b| {
+        b.iter(|| {
+            black_box(
+                words
+                    .iter()
+                    .filter(|w| solver::is_consistent(black_box(&wordle), w))
+                    .count(),
+            )
+        })
+    });
+
+    c.bench_function("filter indexed", |b| {
+        b.iter(|| black_box(index.filter(black_box(&wordle)).len()))
+    });
+}
+
+criterion_group!(benches, filter);
+criterion_main!(benches);
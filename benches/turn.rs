@@ -0,0 +1,49 @@
+/// Benchmarks the game engine's share of a turn: playing a guess, which assesses
+/// every attempt so far to build the board that's rendered in the reply.
+///
+/// wordlebot is a binary crate, so the engine is compiled into the benchmark
+/// directly.
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+
+#[allow(dead_code)]
+#[path = "../src/wordle.rs"]
+mod wordle;
+
+use wordle::Wordle;
+
+/// The guesses of a lost game, chosen to cover repeated and misplaced letters.
+const GUESSES: [&str; 6] = ["crane", "lotus", "hotel", "leapt", "eerie", "belle"];
+
+fn full_game(c: &mut Criterion) {
+    c.bench_function("full game", |b| {
+        b.iter(|| {
+            let mut wordle = Wordle::new("hello".into()).unwrap();
+            for guess in GUESSES {
+                black_box(wordle.play_turn(black_box(guess)).unwrap());
+            }
+        })
+    });
+}
+
+fn last_turn(c: &mut Criterion) {
+    let mut wordle = Wordle::new("hello".into()).unwrap();
+    for guess in &GUESSES[..5] {
+        wordle.play_turn(*guess).unwrap();
+    }
+
+    c.bench_function("last turn", |b| {
+        b.iter(|| {
+            let mut wordle = wordle.clone();
+            black_box(wordle.play_turn(black_box(GUESSES[5])).unwrap());
+        })
+    });
+}
+
+fn assess(c: &mut Criterion) {
+    c.bench_function("assess", |b| {
+        b.iter(|| wordle::assess_pair(black_box("hello"), black_box("leapt")).unwrap())
+    });
+}
+
+criterion_group!(benches, full_game, last_turn, assess);
+criterion_main!(benches);
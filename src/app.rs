@@ -3,11 +3,12 @@
 use anyhow::*;
 use log::*;
 use mobot::{api::User, *};
-use rand::seq::SliceRandom;
+use rand::seq::{IteratorRandom, SliceRandom};
 use std::{
     collections::{HashMap, HashSet},
     fmt::Display,
     sync::Arc,
+    time::{Duration, Instant},
 };
 use tokio::{
     fs::File,
@@ -16,41 +17,197 @@ use tokio::{
 };
 
 use serde::{Deserialize, Serialize};
+use unicode_segmentation::UnicodeSegmentation;
 
+use crate::leaderboard;
+use crate::saveformat::{self, SaveFormat};
+use crate::season::{self, Season, SeasonArchive};
+use crate::settings::UserSettings;
+use crate::solver::{self, Solution, WordIndex};
 use crate::wordle;
 use crate::wordle::Wordle;
+use crate::words;
+use crate::wordstats::{self, WordStats};
 
 pub enum Move {
     Valid,
     InvalidWord,
     InvalidLength,
+    NotLetters,
+    RepeatedLetters,
+    BrokeHardMode(String),
     Won,
     Lost,
+
+    /// The guess was meant for a game that has since been replaced, so it wasn't
+    /// played.
+    GameChanged,
+}
+
+/// TurnResult is everything a reply to a turn needs, gathered with the move so the
+/// handler doesn't have to look the game up again.
+pub struct TurnResult {
+    pub move_: Move,
+
+    /// The board after the move.
+    pub board: wordle::Game,
+
+    /// The target word of the game.
+    pub target: String,
+
+    /// The user's all-time score after the move.
+    pub score: Score,
+
+    /// A letter of the target revealed by the assist, as its position from 1 and the
+    /// letter.
+    pub reveal: Option<(usize, char)>,
+}
+
+/// How long a scanned leaderboard is reused before the save files are read again.
+const LEADERBOARD_TTL: Duration = Duration::from_secs(30);
+
+/// The number of players in the summary announced when a season ends.
+const SEASON_ANNOUNCE_SIZE: usize = 3;
+
+/// The range of attempts players can pick for their games with /tries.
+pub const MAX_ATTEMPTS_RANGE: std::ops::RangeInclusive<usize> = 3..=10;
+
+/// The number of finished games kept for /recent.
+pub const RECENT_GAMES: usize = 10;
+
+/// GameResult is the outcome of a finished game.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct GameResult {
+    pub target: String,
+    pub won: bool,
+    pub guesses: usize,
+    #[serde(default = "wordle::default_max_attempts")]
+    pub max_attempts: usize,
 }
 
+/// How often a user may send /feedback.
+const FEEDBACK_INTERVAL: Duration = Duration::from_secs(60);
+
 /// Score represents a user's score.
-#[derive(Clone, Default, Serialize, Deserialize)]
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
 pub struct Score {
     pub games: u32,
     pub wins: u32,
+
+    /// Losses to rare words, which don't count against the win rate.
+    #[serde(default)]
+    pub forgiven: u32,
+}
+
+impl Score {
+    /// Returns the percentage of games won, leaving out forgiven losses. Players with
+    /// no counted games have a win rate of 0.
+    pub fn win_rate(&self) -> f32 {
+        let counted = self.games.saturating_sub(self.forgiven);
+        if counted == 0 {
+            return 0.0;
+        }
+        self.wins as f32 / counted as f32 * 100.0
+    }
 }
 
 impl Display for Score {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(
             f,
-            "{:.0}% ({}/{})",
-            self.wins as f32 / self.games as f32 * 100.0,
+            "{} ({}/{})",
+            format_percentage(self.wins, self.games.saturating_sub(self.forgiven), 0),
             self.wins,
             self.games
         )
     }
 }
 
+/// format_percentage formats `part` out of `whole` as a percentage with `precision`
+/// decimals. Halves round up, but only a perfect ratio shows as 100%, and only an
+/// empty one as 0%.
+pub fn format_percentage(part: u32, whole: u32, precision: usize) -> String {
+    if whole == 0 {
+        return "0%".into();
+    }
+
+    let scale = 10u64.pow(precision as u32);
+    let (part, whole) = (part as u64, whole as u64);
+    let mut scaled = (part * 100 * scale * 2 + whole) / (whole * 2);
+    if part < whole {
+        scaled = scaled.min(100 * scale - 1);
+    }
+    if part > 0 {
+        scaled = scaled.max(1);
+    }
+
+    if precision == 0 {
+        format!("{}%", scaled)
+    } else {
+        format!(
+            "{}.{:0precision$}%",
+            scaled / scale,
+            scaled % scale,
+            precision = precision
+        )
+    }
+}
+
+/// format_score formats the score for players. Below `min_games` games, the win rate
+/// isn't meaningful yet, so only the raw wins and games are shown.
+pub fn format_score(score: &Score, min_games: u32) -> String {
+    if score.games < min_games {
+        format!("{}/{}", score.wins, score.games)
+    } else {
+        score.to_string()
+    }
+}
+
+/// Sends a message to the admin chat, if there is one.
+async fn send_to_admin_chat(admin_chat_id: &RwLock<Option<i64>>, api: Arc<API>, text: String) {
+    let chat_id = *admin_chat_id.read().await;
+    if let Some(chat_id) = chat_id {
+        _ = api
+            .send_message(&api::SendMessageRequest {
+                chat_id,
+                text: format!("`{}`", api::escape_code(text.as_str())),
+                parse_mode: Some(api::ParseMode::MarkdownV2),
+                ..Default::default()
+            })
+            .await;
+    }
+}
+
+/// format_scoreboard formats the card sent after a win in a group chat, comparing
+/// the winner's score to the average win rate of the chat's players.
+pub fn format_scoreboard(name: &str, score: &Score, average: f32, players: usize) -> String {
+    format!(
+        "Scoreboard: {} {}, group average {:.0}% across {} player{}",
+        name,
+        score,
+        average,
+        players,
+        if players == 1 { "" } else { "s" }
+    )
+}
+
+/// Returns true if any letter appears more than once in the word, ignoring case.
+fn has_repeated_letters(word: &str) -> bool {
+    let mut seen = HashSet::new();
+    !word.to_uppercase().chars().all(|c| seen.insert(c))
+}
+
+/// The current version of the SaveData format. Older saves are upgraded by `migrate`
+/// when loaded.
+const SAVE_VERSION: u32 = 3;
+
 /// SaveData represents the data that is saved for each user on disk. Data
 /// is saved in JSON format.
-#[derive(Serialize, Deserialize)]
+#[derive(Clone, Default, Serialize, Deserialize)]
 struct SaveData {
+    /// Saves from before versioning have no version field, and are version 1.
+    #[serde(default = "SaveData::first_version")]
+    version: u32,
     user_id: String,
     #[serde(default)]
     user_handle: String,
@@ -63,7 +220,58 @@ struct SaveData {
     #[serde(default)]
     played_words: Vec<String>,
     score: Score,
+    #[serde(default)]
+    season: u32,
+    #[serde(default)]
+    season_score: Score,
     last_wordle: Option<Wordle>,
+    #[serde(default)]
+    last_finished: Option<Wordle>,
+    #[serde(default)]
+    recent: Vec<GameResult>,
+    #[serde(default)]
+    settings: UserSettings,
+
+    /// Saves before version 3 kept the settings as top-level fields. `migrate` moves
+    /// them into `settings`.
+    #[serde(flatten, skip_serializing)]
+    legacy_settings: UserSettings,
+}
+
+impl SaveData {
+    fn first_version() -> u32 {
+        1
+    }
+
+    /// Returns empty save data for a user who has never been saved.
+    fn new(user_id: &str, season: u32) -> SaveData {
+        SaveData {
+            version: SAVE_VERSION,
+            user_id: user_id.to_string(),
+            season,
+            ..Default::default()
+        }
+    }
+}
+
+/// migrate upgrades save data from older versions to the current SaveData format.
+fn migrate(mut save_data: SaveData) -> SaveData {
+    if save_data.version < 2 {
+        // Version 1 saves may predate `played_words`, so make sure every won word
+        // also counts as played.
+        for word in &save_data.won_words {
+            if !save_data.played_words.contains(word) {
+                save_data.played_words.push(word.clone());
+            }
+        }
+    }
+
+    if save_data.version < 3 {
+        save_data.settings = save_data.legacy_settings.clone();
+    }
+
+    save_data.version = SAVE_VERSION;
+    save_data
 }
 
 /// AdminSavedate represents the data that is saved for the admin.
@@ -75,6 +283,92 @@ struct AdminSaveData {
     admin_chat_id: Option<i64>,
 }
 
+/// TrackedGame is the last known game of a chat, for admins and reminders.
+struct TrackedGame {
+    wordle: Wordle,
+
+    /// The ID of the user whose save holds the game.
+    user_id: String,
+
+    /// When the chat last played.
+    active: Instant,
+}
+
+/// WordLists holds the default target words and the valid words. It's shared by all
+/// chats and replaced as a whole on /reload, so a chat never sees a half-loaded list.
+#[derive(Default)]
+struct WordLists {
+    targets: Vec<String>,
+
+    /// The distinct target word lengths, in ascending order.
+    target_lengths: Vec<usize>,
+
+    valid: HashSet<String>,
+    solver_index: WordIndex,
+
+    /// The unaccented forms of valid words with accents, for accent-insensitive games.
+    folded: HashSet<String>,
+
+    /// The uppercase target words that weren't in the valid words as loaded, skipped
+    /// under strict targets.
+    unlisted_targets: HashSet<String>,
+}
+
+impl WordLists {
+    /// Builds the word lists. The target words and `other_targets`, e.g. the words of
+    /// game modes, are added to the valid words, so every target is a valid guess.
+    fn new<'a>(
+        targets: Vec<String>,
+        mut valid: HashSet<String>,
+        other_targets: impl Iterator<Item = &'a String>,
+    ) -> WordLists {
+        let mut all_targets = other_targets.cloned().collect::<Vec<_>>();
+        all_targets.extend(targets.iter().cloned());
+        let unlisted_targets = all_targets
+            .iter()
+            .filter(|w| !valid.contains(&w.to_ascii_lowercase()))
+            .map(|w| w.to_ascii_uppercase())
+            .collect();
+        words::add_targets(&mut valid, &all_targets);
+
+        WordLists {
+            unlisted_targets,
+            target_lengths: target_lengths(&targets),
+            targets,
+            solver_index: WordIndex::new(valid.iter().cloned()),
+            folded: valid
+                .iter()
+                .map(|w| wordle::fold_accents(w))
+                .filter(|w| !valid.contains(w))
+                .collect(),
+            valid,
+        }
+    }
+}
+
+/// Returns the first letter of the target that no attempt has in the right spot, as
+/// its position from 1 and the letter.
+fn unsolved_letter(game: &wordle::Game, target_word: &str) -> Option<(usize, char)> {
+    target_word.chars().enumerate().find_map(|(i, c)| {
+        let solved = game
+            .attempts
+            .iter()
+            .any(|a| matches!(a.get(i), Some(wordle::Letter::Correct(_))));
+        (!solved).then_some((i + 1, c))
+    })
+}
+
+/// Returns the distinct lengths of the target words, in ascending order.
+fn target_lengths(targets: &[String]) -> Vec<usize> {
+    let mut lengths = targets.iter().map(|w| w.len()).collect::<Vec<_>>();
+    lengths.sort();
+    lengths.dedup();
+    lengths
+}
+
+/// A leaderboard scan and when it was taken.
+type CachedLeaderboard = (Instant, Vec<leaderboard::Entry>);
+
 /// App represents the bot state for the wordle bot.
 #[derive(Clone, Default, BotState)]
 pub struct App {
@@ -82,15 +376,68 @@ pub struct App {
     pub game_name: String,
     pub admin_user: Option<String>,
     admin_chat_id: Arc<RwLock<Option<i64>>>,
+    analytics_default: bool,
+    accent_insensitive: bool,
+    combined_words_file: Option<String>,
+    avoid_similar_targets: bool,
+    debug_updates: bool,
+    duplicate_window: Duration,
+    feedback_times: Arc<RwLock<HashMap<i64, Instant>>>,
+    last_messages: Arc<RwLock<HashMap<i64, (String, Instant)>>>,
+    first_message_counts: bool,
+    giveup_counts: bool,
+    hard_mode: wordle::HardMode,
+    max_word_length: usize,
+    mercy_frequency: Option<u64>,
+    min_games_for_rate: u32,
+    post_win_cooldown: Duration,
+    reminder_after: Option<Duration>,
+    reminder_activity: Arc<RwLock<HashMap<i64, Instant>>>,
+    utc_offset: Option<i32>,
+    win_emoji: String,
+    lose_emoji: String,
+    no_emoji: bool,
+    no_repeated_letters: bool,
+    quiet_admin: bool,
+    strict_targets: bool,
+    games: Arc<RwLock<HashMap<i64, TrackedGame>>>,
+    max_games: Option<usize>,
+    chat_players: Arc<RwLock<HashMap<i64, HashSet<String>>>>,
+    hide_targets: bool,
+    hide_word_on_loss: bool,
+    held_back_words: Arc<RwLock<HashMap<i64, Vec<String>>>>,
+    leaderboard: Arc<RwLock<Option<CachedLeaderboard>>>,
+    players: Arc<RwLock<HashMap<String, User>>>,
+    modes: Arc<HashMap<String, Vec<String>>>,
+    paused: Arc<RwLock<bool>>,
     save_dir: String,
+    save_delay: Duration,
+    save_format: SaveFormat,
+    pending_saves: Arc<RwLock<HashMap<String, SaveData>>>,
     scores: Arc<RwLock<HashMap<String, Score>>>,
-    target_words: Arc<Vec<String>>,
-    valid_words: Arc<HashSet<String>>,
+    season: Arc<RwLock<Season>>,
+    season_announce: bool,
+    season_length: Option<Duration>,
+    season_scores: Arc<RwLock<HashMap<String, Score>>>,
+    started_at: Option<Instant>,
+    target_words_file: String,
+    valid_words_files: Vec<String>,
+    words: Arc<std::sync::RwLock<Arc<WordLists>>>,
+    word_frequencies: Arc<HashMap<String, u64>>,
+    word_stats: Arc<RwLock<HashMap<String, WordStats>>>,
 
     // Per chat ID
     pub wordle: Option<Wordle>,
+    game_serial: u64,
+    last_finished: Option<Wordle>,
+    last_win: Option<Instant>,
+    after_score: bool,
+    mode: Option<String>,
     played_words: HashSet<String>,
+    recent: Vec<GameResult>,
+    settings: UserSettings,
     won_words: HashSet<String>,
+    released: bool,
 }
 
 impl App {
@@ -98,7 +445,17 @@ impl App {
     pub fn new(game_name: String, target_words: Vec<String>) -> App {
         App {
             game_name,
-            target_words: Arc::new(target_words),
+            analytics_default: true,
+            giveup_counts: true,
+            started_at: Some(Instant::now()),
+            win_emoji: "\u{1F46F}".into(),
+            lose_emoji: "\u{1F979}".into(),
+            max_word_length: wordle::MAX_WORD_LENGTH,
+            words: Arc::new(std::sync::RwLock::new(Arc::new(WordLists {
+                target_lengths: target_lengths(&target_words),
+                targets: target_words,
+                ..Default::default()
+            }))),
             ..Default::default()
         }
     }
@@ -108,28 +465,218 @@ impl App {
             return false;
         }
 
-        match self.wordle.as_ref().unwrap().game().unwrap().state {
-            wordle::State::Playing => true,
-            _ => false,
-        }
+        matches!(
+            self.wordle.as_ref().unwrap().game().unwrap().state,
+            wordle::State::Playing
+        )
     }
 
     pub async fn start_game(&mut self) -> Result<String> {
-        // Get the sender's first name
-        let target_word = self
-            .target_words
+        let target_word = self.pick_target()?;
+        self.set_game(&target_word, false)?;
+        self.update_word_stats(&target_word, |stats| stats.serves += 1)
+            .await;
+        Ok(target_word)
+    }
+
+    /// Picks the target word of the chat's next game.
+    fn pick_target(&self) -> Result<String> {
+        // Only consider targets of the preferred length, if any. Targets that repeat a
+        // letter can't be guessed under the no-repeats rule.
+        let words = self.words();
+        let candidates = self
+            .target_pool(&words)
+            .iter()
+            .filter(|w| self.settings.word_length.is_none_or(|len| w.len() == len))
+            .filter(|w| w.chars().count() <= self.max_word_length)
+            .filter(|w| !self.no_repeated_letters || !has_repeated_letters(w))
+            .filter(|w| {
+                !self.strict_targets || !words.unlisted_targets.contains(&w.to_ascii_uppercase())
+            })
+            .collect::<Vec<_>>();
+
+        if candidates.is_empty() && self.settings.word_length.is_some() {
+            bail!("no words of that length available");
+        }
+
+        // Optionally avoid targets that are nearly the same as the previous one.
+        let previous = self.wordle.as_ref().map(|w| w.target_word.clone());
+        let is_distinct = |w: &str| {
+            !self.avoid_similar_targets
+                || previous
+                    .as_ref()
+                    .is_none_or(|p| !wordle::within_one_edit(p, &w.to_uppercase()))
+        };
+        let is_unplayed = |w: &str| !self.played_words.contains(&w.to_ascii_uppercase());
+
+        // Scan the list for an unplayed word, or pick a random one.
+        let target_word = candidates
             .iter()
-            .find(|&w| !self.played_words.contains(&w.to_ascii_uppercase()))
-            .or_else(|| self.target_words.choose(&mut rand::thread_rng()))
+            .find(|w| is_unplayed(w) && is_distinct(w))
+            .or_else(|| candidates.iter().find(|w| is_unplayed(w)))
+            .or_else(|| candidates.choose(&mut rand::thread_rng()))
             .ok_or(anyhow!("no target words found"))?
-            .clone()
             .to_uppercase();
 
-        self.wordle = Some(Wordle::new(target_word.clone())?);
-        self.played_words.insert(target_word.clone());
         Ok(target_word)
     }
 
+    /// Starts a game with the given target word, with the chat's options. Practice
+    /// games don't count as played words.
+    fn set_game(&mut self, target_word: &str, practice: bool) -> Result<()> {
+        self.wordle = Some(
+            Wordle::new(target_word.to_string())?
+                .with_fold_accents(self.accent_insensitive)
+                .with_max_attempts(self.max_attempts())
+                .with_practice(practice),
+        );
+        self.game_serial += 1;
+        if !practice {
+            self.played_words.insert(target_word.to_string());
+        }
+        Ok(())
+    }
+
+    /// Starts a practice game with the given target word, for admins to try out a
+    /// word. Practice games don't count toward scores or word stats.
+    pub fn start_practice(&mut self, target_word: &str) -> Result<String> {
+        let target_word = target_word.to_uppercase();
+        self.set_game(&target_word, true)?;
+        Ok(target_word)
+    }
+
+    /// Returns true if the current game is a practice game.
+    pub fn is_practice(&self) -> bool {
+        self.wordle.as_ref().is_some_and(|w| w.practice)
+    }
+
+    /// Returns a number that identifies the chat's current game. It changes whenever
+    /// a new game starts, so a guess can be checked against the game it was meant for.
+    pub fn game_serial(&self) -> u64 {
+        self.game_serial
+    }
+
+    /// Returns the target words of the chat's current mode, or the default targets if
+    /// it has none.
+    fn target_pool<'a>(&'a self, words: &'a WordLists) -> &'a Vec<String> {
+        self.mode
+            .as_ref()
+            .and_then(|mode| self.modes.get(mode))
+            .unwrap_or(&words.targets)
+    }
+
+    /// Returns the current word lists. The lists are a snapshot, unaffected by later
+    /// reloads.
+    fn words(&self) -> Arc<WordLists> {
+        self.words.read().unwrap().clone()
+    }
+
+    /// Adds a game mode with its own target words, played with `/new<name>`.
+    pub fn add_mode(&mut self, name: String, target_words: Vec<String>) {
+        Arc::make_mut(&mut self.modes).insert(name, target_words);
+    }
+
+    /// Returns the names of the game modes, in ascending order.
+    pub fn mode_names(&self) -> Vec<String> {
+        let mut names = self.modes.keys().cloned().collect::<Vec<_>>();
+        names.sort();
+        names
+    }
+
+    /// Sets the chat's current mode, used by the next games. If `None`, the default
+    /// targets are used.
+    pub fn set_mode(&mut self, mode: Option<String>) -> Result<()> {
+        if let Some(mode) = &mode {
+            if !self.modes.contains_key(mode) {
+                bail!("there's no {} game", mode);
+            }
+        }
+
+        self.mode = mode;
+        Ok(())
+    }
+
+    /// Starts a game with the given target word, e.g. from a challenge. The word must
+    /// be a valid word or a target, no longer than the maximum word length.
+    pub async fn start_game_with(&mut self, target_word: String) -> Result<String> {
+        let words = self.words();
+        let known = |w: &String| w.eq_ignore_ascii_case(&target_word);
+        if !words.valid.contains(&target_word.to_lowercase())
+            && !words.targets.iter().any(known)
+            && !self.modes.values().flatten().any(known)
+        {
+            bail!("that challenge isn't a word I know");
+        }
+        if target_word.chars().count() > self.max_word_length {
+            bail!("that challenge is too long to play here");
+        }
+
+        let target_word = target_word.to_uppercase();
+        self.set_game(&target_word, false)?;
+        self.update_word_stats(&target_word, |stats| stats.serves += 1)
+            .await;
+        Ok(target_word)
+    }
+
+    /// Sets the preferred target word length for the next games. If `None`, targets
+    /// of any length are picked.
+    pub fn set_word_length(&mut self, word_length: Option<usize>) -> Result<()> {
+        if let Some(len) = word_length {
+            if !self.words().targets.iter().any(|w| w.len() == len) {
+                bail!("no {}-letter words available", len);
+            }
+        }
+
+        self.settings.word_length = word_length;
+        Ok(())
+    }
+
+    /// Sets the longest target word picked for games, up to wordle::MAX_WORD_LENGTH.
+    /// Longer words in the lists are skipped.
+    pub fn set_max_word_length(&mut self, max_word_length: usize) -> Result<()> {
+        if !(3..=wordle::MAX_WORD_LENGTH).contains(&max_word_length) {
+            bail!(
+                "the maximum word length must be between 3 and {}",
+                wordle::MAX_WORD_LENGTH
+            );
+        }
+
+        self.max_word_length = max_word_length;
+        Ok(())
+    }
+
+    /// Returns the number of attempts the user gets in their next games.
+    pub fn max_attempts(&self) -> usize {
+        self.settings.max_attempts.unwrap_or(wordle::MAX_ATTEMPTS)
+    }
+
+    /// Sets the number of attempts the user gets in their next games, within
+    /// MAX_ATTEMPTS_RANGE. If `None`, they get MAX_ATTEMPTS.
+    pub fn set_max_attempts(&mut self, max_attempts: Option<usize>) -> Result<()> {
+        if let Some(n) = max_attempts {
+            if !MAX_ATTEMPTS_RANGE.contains(&n) {
+                bail!(
+                    "the number of guesses must be between {} and {}",
+                    MAX_ATTEMPTS_RANGE.start(),
+                    MAX_ATTEMPTS_RANGE.end()
+                );
+            }
+        }
+
+        self.settings.max_attempts = max_attempts;
+        Ok(())
+    }
+
+    /// Returns the default target words.
+    pub fn target_words(&self) -> Vec<String> {
+        self.words().targets.clone()
+    }
+
+    /// Returns the distinct target word lengths, in ascending order.
+    pub fn word_lengths(&self) -> Vec<usize> {
+        self.words().target_lengths.clone()
+    }
+
     /// Authorizes the user as an admin.
     pub async fn auth_admin(&mut self, username: &str, chat_id: i64) -> Result<bool> {
         if self.admin_user.is_some() && self.admin_user.as_ref().unwrap().eq(username) {
@@ -197,166 +744,1682 @@ impl App {
         Ok(())
     }
 
-    /// Sends a log message to the admin chat
-    pub async fn admin_log(&self, api: Arc<API>, text: String) {
-        let chat_id = *self.admin_chat_id.read().await;
-        if let Some(chat_id) = chat_id {
-            _ = api
-                .send_message(&api::SendMessageRequest {
+    /// Returns true if the user is the configured admin.
+    pub fn is_admin(&self, user: &User) -> bool {
+        self.admin_user.is_some() && self.admin_user == user.username
+    }
+
+    /// Records the current game of the given chat, played by `from`, so admins can
+    /// look it up. Finished games are dropped, so only games in progress are kept.
+    /// Past the game cap, the least recently active chat's game is persisted and
+    /// evicted.
+    pub async fn track_game(&self, chat_id: i64, from: &User) {
+        if !self.is_playing() {
+            self.games.write().await.remove(&chat_id);
+        } else if let Some(wordle) = &self.wordle {
+            let evicted = {
+                let mut games = self.games.write().await;
+                games.insert(
                     chat_id,
-                    text: format!("`{}`", api::escape_code(text.as_str())),
-                    parse_mode: Some(api::ParseMode::MarkdownV2),
-                    ..Default::default()
-                })
-                .await;
+                    TrackedGame {
+                        wordle: wordle.clone(),
+                        user_id: from.id.to_string(),
+                        active: Instant::now(),
+                    },
+                );
+                self.evict_games(&mut games, chat_id)
+            };
+            for (evicted_chat_id, evicted) in evicted {
+                self.reminder_activity
+                    .write()
+                    .await
+                    .remove(&evicted_chat_id);
+                self.persist(&evicted.user_id).await;
+            }
         }
-    }
 
-    /// Returns true if the word is a valid word.
-    pub fn is_valid_word(&self, word: String) -> bool {
-        self.valid_words.is_empty() || self.valid_words.contains(&word.to_ascii_lowercase())
+        // Only games in progress of players who opted in get reminders.
+        let mut activity = self.reminder_activity.write().await;
+        if self.settings.reminders && self.is_playing() {
+            activity.insert(chat_id, Instant::now());
+        } else {
+            activity.remove(&chat_id);
+        }
     }
 
-    /// Set the valid words for this game.
-    pub fn set_valid_words(&mut self, valid_words: HashSet<String>) {
-        self.valid_words = Arc::new(valid_words);
+    /// Removes the least recently active games past the game cap, and returns them.
+    /// The game of `current_chat_id`, which was just played, is never evicted.
+    fn evict_games(
+        &self,
+        games: &mut HashMap<i64, TrackedGame>,
+        current_chat_id: i64,
+    ) -> Vec<(i64, TrackedGame)> {
+        let mut evicted = vec![];
+        let Some(max_games) = self.max_games else {
+            return evicted;
+        };
+
+        while games.len() > max_games {
+            let Some(oldest) = games
+                .iter()
+                .filter(|(chat_id, _)| **chat_id != current_chat_id)
+                .min_by_key(|(_, game)| game.active)
+                .map(|(chat_id, _)| *chat_id)
+            else {
+                break;
+            };
+            evicted.extend(games.remove_entry(&oldest));
+        }
+        evicted
     }
 
-    /// Set the directory where game state is saved.
-    pub fn set_save_dir(&mut self, save_dir: String) {
-        self.save_dir = save_dir;
+    /// Writes the user's pending save to disk, if there is one.
+    async fn persist(&self, user_id: &str) {
+        let pending = self.pending_saves.write().await.remove(user_id);
+        if let Some(save_data) = pending {
+            if let Err(e) = self.write_save(&save_data).await {
+                error!("Error saving game state: {}", e);
+            }
+        }
     }
 
-    /// Returns the user's current score
-    pub async fn score(&self, from: &String) -> Score {
-        self.scores
+    /// Returns the game in progress of the given chat, if it's known.
+    pub async fn tracked_game(&self, chat_id: i64) -> Option<Wordle> {
+        self.games
             .read()
             .await
-            .get(from)
-            .cloned()
-            .unwrap_or_default()
+            .get(&chat_id)
+            .map(|game| game.wordle.clone())
     }
 
-    /// Increments the number of games this user played and saves state.
-    pub async fn inc_games(&self, from: &User) {
-        self.scores
-            .write()
+    /// Returns the number of tracked games still in progress.
+    pub async fn active_games(&self) -> usize {
+        self.games
+            .read()
             .await
-            .entry(from.id.to_string())
-            .or_default()
-            .games += 1;
-        if let Err(e) = self.save(from).await {
-            error!("Error saving game state: {}", e);
-        }
+            .values()
+            .filter(|game| {
+                game.wordle
+                    .game()
+                    .is_ok_and(|g| g.state == wordle::State::Playing)
+            })
+            .count()
     }
 
-    /// Increments the number of wins for this user and saves state.
-    pub async fn inc_wins(&mut self, from: &User) {
-        self.scores
-            .write()
-            .await
-            .entry(from.id.to_string())
-            .or_default()
-            .wins += 1;
-        self.won_words
-            .insert(self.wordle.as_ref().unwrap().target_word.clone());
-        if let Err(e) = self.save(from).await {
-            error!("Error saving game state: {}", e);
-        }
+    /// Returns how long the bot has been running.
+    pub fn uptime(&self) -> Duration {
+        self.started_at.map(|t| t.elapsed()).unwrap_or_default()
     }
 
-    /// Plays a turn for the user with the given word.
-    pub async fn play_turn(&mut self, from: &User, word: String) -> anyhow::Result<Move> {
-        if !self.is_valid_word(word.clone()) {
-            return Ok(Move::InvalidWord);
-        }
+    /// Returns true if target words are left out of admin logs.
+    pub fn hide_targets(&self) -> bool {
+        self.hide_targets
+    }
 
-        if word.len() != self.wordle.as_ref().unwrap().target_word.len() {
-            return Ok(Move::InvalidLength);
-        }
+    /// Set whether target words are left out of admin logs. Admins can still
+    /// look them up with /peek.
+    pub fn set_hide_targets(&mut self, hide_targets: bool) {
+        self.hide_targets = hide_targets;
+    }
 
-        let game = self.wordle.as_mut().unwrap().play_turn(word)?;
+    /// Set whether giving up counts as a lost game. If not, a game given up doesn't
+    /// count toward the score at all.
+    pub fn set_giveup_counts(&mut self, giveup_counts: bool) {
+        self.giveup_counts = giveup_counts;
+    }
 
-        if let Err(e) = self.save(&from).await {
-            error!("Error saving game state: {}", e);
-        }
+    /// Returns true if the target word is left out of the reply to a lost game.
+    pub fn hide_word_on_loss(&self) -> bool {
+        self.hide_word_on_loss
+    }
 
-        match game.state {
-            wordle::State::Won => {
-                self.inc_wins(&from).await;
-                Ok(Move::Won)
-            }
-            wordle::State::Lost => Ok(Move::Lost),
-            _ => Ok(Move::Valid),
+    /// Set whether the target word is left out of the reply to a lost game. The words
+    /// are held back until an admin reveals them with /reveal.
+    pub fn set_hide_word_on_loss(&mut self, hide_word_on_loss: bool) {
+        self.hide_word_on_loss = hide_word_on_loss;
+    }
+
+    /// Holds back the word the chat lost to, until `reveal_words`. The held back
+    /// words are saved, so they survive a restart.
+    pub async fn hold_back_word(&self, chat_id: i64, word: &str) {
+        let mut held_back = self.held_back_words.write().await;
+        let words = held_back.entry(chat_id).or_default();
+        if !words.iter().any(|w| w == word) {
+            words.push(word.to_string());
+        }
+        if let Err(e) = self.save_held_back_words(&held_back).await {
+            error!("Error saving held back words: {}", e);
         }
     }
 
-    /// Save game state for user
-    pub async fn save(&self, user: &User) -> anyhow::Result<()> {
+    /// Loads the held back words from the save directory.
+    pub async fn load_held_back_words(&self) -> Result<()> {
         if self.save_dir.is_empty() {
             return Ok(());
         }
 
-        let filename = format!("{}/{}.json", self.save_dir, user.id);
-
-        let mut file = File::create(filename.clone())
+        let filename = format!("{}/held-back.json", self.save_dir);
+        let contents = tokio::fs::read(&filename)
             .await
-            .context(format!("Error creating file {}", filename))?;
-
-        let last_wordle = self.wordle.clone();
+            .context(format!("Error reading file {}", filename))?;
+        *self.held_back_words.write().await = serde_json::from_slice(&contents)
+            .context(format!("Error deserializing file {}", filename))?;
+        Ok(())
+    }
 
-        let save_data = SaveData {
-            user_id: user.id.clone().to_string(),
-            user_handle: user.username.clone().unwrap_or_default(),
-            user_first_name: user.first_name.clone(),
-            user_last_name: user.last_name.clone().unwrap_or_default(),
-            played_words: self.played_words.iter().cloned().collect(),
-            won_words: self.won_words.iter().cloned().collect(),
-            score: self.score(&user.id.to_string()).await,
-            last_wordle,
-        };
+    /// Saves the held back words to the save directory.
+    async fn save_held_back_words(&self, held_back: &HashMap<i64, Vec<String>>) -> Result<()> {
+        if self.save_dir.is_empty() {
+            return Ok(());
+        }
 
-        file.write_all(
-            serde_json::to_vec(&save_data)
-                .context("Error serializing game state")?
-                .as_ref(),
+        let filename = format!("{}/held-back.json", self.save_dir);
+        tokio::fs::write(
+            &filename,
+            serde_json::to_vec(held_back).context("Error serializing held back words")?,
         )
         .await
         .context(format!("Error writing file {}", filename))
     }
 
-    /// Load game state for user.
-    pub async fn load(&mut self, user: &User) -> anyhow::Result<()> {
-        if self.save_dir.is_empty() {
-            bail!("No save directory configured");
-        }
-
-        let filename = format!("{}/{}.json", self.save_dir, user.id);
-
-        let mut file = File::open(filename.clone())
+    /// Returns true if the word the chat lost to is still held back.
+    pub async fn is_held_back(&self, chat_id: i64, word: &str) -> bool {
+        self.held_back_words
+            .read()
             .await
-            .context(format!("Error opening file {}", filename))?;
+            .get(&chat_id)
+            .is_some_and(|words| words.iter().any(|w| w == word))
+    }
 
-        let mut contents = vec![];
-        file.read_to_end(&mut contents)
+    /// Sends every chat the words it lost to that were held back, and returns the
+    /// number of chats messaged. Words are only released once their chat got them,
+    /// so chats that couldn't be messaged get them on the next reveal.
+    pub async fn reveal_words(&self, api: Arc<API>) -> usize {
+        let held_back = self.held_back_words.read().await.clone();
+
+        let mut revealed = vec![];
+        for (chat_id, words) in held_back {
+            let text = match words.as_slice() {
+                [word] => format!("The puzzle is closed! The word was {}.", word),
+                _ => format!("The puzzle is closed! The words were {}.", words.join(", ")),
+            };
+            let result = api
+                .send_message(&api::SendMessageRequest {
+                    chat_id,
+                    text,
+                    ..Default::default()
+                })
+                .await;
+            if let Err(e) = result {
+                warn!("Could not reveal words to chat {}: {}", chat_id, e);
+                continue;
+            }
+            revealed.push((chat_id, words));
+        }
+
+        // Words held back while the messages were sent stay held back.
+        let mut held_back = self.held_back_words.write().await;
+        for (chat_id, words) in &revealed {
+            if let Some(held) = held_back.get_mut(chat_id) {
+                held.retain(|w| !words.contains(w));
+                if held.is_empty() {
+                    held_back.remove(chat_id);
+                }
+            }
+        }
+        if let Err(e) = self.save_held_back_words(&held_back).await {
+            error!("Error saving held back words: {}", e);
+        }
+
+        revealed.len()
+    }
+
+    /// Returns the number of games a player needs before their win rate is shown.
+    pub fn min_games_for_rate(&self) -> u32 {
+        self.min_games_for_rate
+    }
+
+    /// Set the number of games a player needs before their win rate is shown.
+    pub fn set_min_games_for_rate(&mut self, min_games_for_rate: u32) {
+        self.min_games_for_rate = min_games_for_rate;
+    }
+
+    /// Returns the user's settings, as loaded.
+    pub fn settings(&self) -> &UserSettings {
+        &self.settings
+    }
+
+    /// Returns true if the user asked the bot to keep its comments polite.
+    pub fn polite(&self) -> bool {
+        self.settings.polite
+    }
+
+    /// Set whether the bot keeps its comments polite for the user.
+    pub fn set_polite(&mut self, polite: bool) {
+        self.settings.polite = polite;
+    }
+
+    /// Returns true if wrong letters are hidden behind spoiler markup.
+    pub fn spoilers(&self) -> bool {
+        self.settings.spoilers
+    }
+
+    /// Set whether wrong letters are hidden behind spoiler markup.
+    pub fn set_spoilers(&mut self, spoilers: bool) {
+        self.settings.spoilers = spoilers;
+    }
+
+    /// Returns true if correct letters are rendered as plain letters instead of emoji,
+    /// either by the user's choice or because the bot doesn't use emoji.
+    pub fn plain_letters(&self) -> bool {
+        self.no_emoji || self.settings.plain_letters
+    }
+
+    /// Set whether correct letters are rendered as plain letters instead of emoji.
+    pub fn set_plain_letters(&mut self, plain_letters: bool) {
+        self.settings.plain_letters = plain_letters;
+    }
+
+    /// Returns true if the user wants replies to guesses without comments or legend.
+    pub fn terse(&self) -> bool {
+        self.settings.terse
+    }
+
+    /// Set whether the user gets replies to guesses without comments or legend.
+    pub fn set_terse(&mut self, terse: bool) {
+        self.settings.terse = terse;
+    }
+
+    /// Returns true if the user gets a letter revealed halfway through a game.
+    pub fn assist(&self) -> bool {
+        self.settings.assist
+    }
+
+    /// Set whether the user gets a letter revealed halfway through a game.
+    pub fn set_assist(&mut self, assist: bool) {
+        self.settings.assist = assist;
+    }
+
+    /// Returns true if the user wants reminders about games they left unfinished.
+    pub fn reminders(&self) -> bool {
+        self.settings.reminders
+    }
+
+    /// Set whether the user wants reminders about games they left unfinished.
+    pub fn set_reminders(&mut self, reminders: bool) {
+        self.settings.reminders = reminders;
+    }
+
+    /// Returns how long a game must be left alone before its player is reminded. If
+    /// `None`, there are no reminders.
+    pub fn reminder_after(&self) -> Option<Duration> {
+        self.reminder_after
+    }
+
+    /// Set how long a game must be left alone before its player is reminded. If
+    /// `None`, there are no reminders.
+    pub fn set_reminder_after(&mut self, reminder_after: Option<Duration>) {
+        self.reminder_after = reminder_after;
+    }
+
+    /// Reminds players who opted in about games left alone for `reminder_after` as of
+    /// `now`. Each game is reminded about once, until it's played again. Returns the
+    /// number of reminders sent.
+    pub async fn send_reminders(&self, api: Arc<API>, now: Instant) -> usize {
+        let Some(reminder_after) = self.reminder_after else {
+            return 0;
+        };
+
+        let stale = {
+            let mut activity = self.reminder_activity.write().await;
+            let stale = activity
+                .iter()
+                .filter(|(_, last)| now.saturating_duration_since(**last) >= reminder_after)
+                .map(|(chat_id, _)| *chat_id)
+                .collect::<Vec<_>>();
+            stale.iter().for_each(|chat_id| {
+                activity.remove(chat_id);
+            });
+            stale
+        };
+
+        let mut sent = 0;
+        for chat_id in stale {
+            let playing = self
+                .tracked_game(chat_id)
+                .await
+                .is_some_and(|w| w.game().is_ok_and(|g| g.state == wordle::State::Playing));
+            if !playing {
+                continue;
+            }
+
+            let result = api
+                .send_message(&api::SendMessageRequest {
+                    chat_id,
+                    text: "Your game is still waiting for you! Send a guess to pick up where you left off. Type /reminders off to stop these.".into(),
+                    ..Default::default()
+                })
+                .await;
+            if let Err(e) = result {
+                warn!("Could not send reminder to chat {}: {}", chat_id, e);
+                continue;
+            }
+            sent += 1;
+        }
+        sent
+    }
+
+    /// Spawns a background task that sends reminders about games left alone. Does
+    /// nothing if there are no reminders.
+    pub fn spawn_reminders(&self, api: Arc<API>) {
+        if self.reminder_after.is_none() {
+            return;
+        }
+
+        let app = self.clone();
+        tokio::spawn(async move {
+            loop {
+                app.send_reminders(Arc::clone(&api), Instant::now()).await;
+                tokio::time::sleep(Duration::from_secs(60)).await;
+            }
+        });
+    }
+
+    /// Returns true if the user's events are forwarded to the admin log.
+    pub fn analytics(&self) -> bool {
+        self.settings.analytics.unwrap_or(self.analytics_default)
+    }
+
+    /// Set whether the user's events are forwarded to the admin log. If None, the
+    /// default applies.
+    pub fn set_analytics(&mut self, analytics: Option<bool>) {
+        self.settings.analytics = analytics;
+    }
+
+    /// Set whether events are forwarded to the admin log for users that haven't
+    /// chosen.
+    pub fn set_analytics_default(&mut self, analytics_default: bool) {
+        self.analytics_default = analytics_default;
+    }
+
+    /// Sends a log message to the admin chat, unless the user opted out of analytics.
+    /// The returned future doesn't borrow the app, so it can be awaited after the lock
+    /// on the app is released.
+    pub fn admin_log(
+        &self,
+        api: Arc<API>,
+        text: String,
+    ) -> impl std::future::Future<Output = ()> + Send + 'static {
+        let admin_chat_id = self.analytics().then(|| Arc::clone(&self.admin_chat_id));
+        async move {
+            if let Some(admin_chat_id) = admin_chat_id {
+                send_to_admin_chat(&admin_chat_id, api, text).await;
+            }
+        }
+    }
+
+    /// Returns the bot's offset from UTC in hours, if set.
+    pub fn utc_offset(&self) -> Option<i32> {
+        self.utc_offset
+    }
+
+    /// Set the bot's offset from UTC in hours, used to greet players by time of day.
+    pub fn set_utc_offset(&mut self, utc_offset: Option<i32>) {
+        self.utc_offset = utc_offset;
+    }
+
+    /// Returns true if replies use ASCII instead of emoji.
+    pub fn no_emoji(&self) -> bool {
+        self.no_emoji
+    }
+
+    /// Set whether replies use ASCII instead of emoji, for terminals and logs that
+    /// can't show them. This overrides the user's choice of letters.
+    pub fn set_no_emoji(&mut self, no_emoji: bool) {
+        self.no_emoji = no_emoji;
+    }
+
+    /// Returns the emoji shown when a game is won.
+    pub fn win_emoji(&self) -> &str {
+        if self.no_emoji {
+            return ":D";
+        }
+        &self.win_emoji
+    }
+
+    /// Returns the emoji shown when a game is lost.
+    pub fn lose_emoji(&self) -> &str {
+        if self.no_emoji {
+            return ":(";
+        }
+        &self.lose_emoji
+    }
+
+    /// Set the emoji shown when a game is won or lost. If `None`, the defaults are
+    /// kept. Each must be a single grapheme, so it reads as one emoji.
+    pub fn set_celebration_emoji(
+        &mut self,
+        win_emoji: Option<String>,
+        lose_emoji: Option<String>,
+    ) -> Result<()> {
+        for emoji in [&win_emoji, &lose_emoji].into_iter().flatten() {
+            if emoji.graphemes(true).count() != 1 {
+                bail!("{:?} is not a single emoji", emoji);
+            }
+        }
+
+        self.win_emoji = win_emoji.unwrap_or(self.win_emoji.clone());
+        self.lose_emoji = lose_emoji.unwrap_or(self.lose_emoji.clone());
+        Ok(())
+    }
+
+    /// Set how long after a win plain messages don't start a new game.
+    pub fn set_post_win_cooldown(&mut self, post_win_cooldown: Duration) {
+        self.post_win_cooldown = post_win_cooldown;
+    }
+
+    /// Returns true if the chat won a game within the post-win cooldown.
+    pub fn in_post_win_cooldown(&self) -> bool {
+        self.last_win
+            .is_some_and(|last_win| last_win.elapsed() < self.post_win_cooldown)
+    }
+
+    /// Set how soon a repeat of a chat's previous message is ignored as a double-send.
+    /// If zero, repeats are never ignored.
+    pub fn set_duplicate_window(&mut self, duplicate_window: Duration) {
+        self.duplicate_window = duplicate_window;
+    }
+
+    /// Records the chat's message, and returns true if it repeats the chat's previous
+    /// message within the duplicate window, like clients that double-send.
+    pub async fn is_duplicate(&self, chat_id: i64, text: &str, now: Instant) -> bool {
+        if self.duplicate_window.is_zero() {
+            return false;
+        }
+
+        let mut last_messages = self.last_messages.write().await;
+        let duplicate = last_messages.get(&chat_id).is_some_and(|(last_text, at)| {
+            last_text == text && now.saturating_duration_since(*at) < self.duplicate_window
+        });
+        last_messages.insert(chat_id, (text.to_string(), now));
+        duplicate
+    }
+
+    /// Records that the user finished a game in the given chat, for the chat's
+    /// scoreboard.
+    pub async fn add_chat_player(&self, chat_id: i64, from: &User) {
+        self.chat_players
+            .write()
+            .await
+            .entry(chat_id)
+            .or_default()
+            .insert(from.id.to_string());
+    }
+
+    /// Returns the average win rate of the players who finished a game in the given
+    /// chat since the bot started, and how many of them have a score. Players whose
+    /// score isn't loaded are left out of both.
+    pub async fn chat_score(&self, chat_id: i64) -> (f32, usize) {
+        let chat_players = self.chat_players.read().await;
+        let Some(players) = chat_players.get(&chat_id) else {
+            return (0.0, 0);
+        };
+
+        let scores = self.scores.read().await;
+        let rates = players
+            .iter()
+            .filter_map(|id| scores.get(id))
+            .map(Score::win_rate)
+            .collect::<Vec<_>>();
+        if rates.is_empty() {
+            return (0.0, 0);
+        }
+        (rates.iter().sum::<f32>() / rates.len() as f32, rates.len())
+    }
+
+    /// Records whether the chat's last command was /score, which loads the last game
+    /// even if it's over.
+    pub fn set_after_score(&mut self, after_score: bool) {
+        self.after_score = after_score;
+    }
+
+    /// Returns true if the chat's last command was /score, and clears it.
+    pub fn take_after_score(&mut self) -> bool {
+        std::mem::take(&mut self.after_score)
+    }
+
+    /// Set whether guesses with a repeated letter are rejected.
+    pub fn set_no_repeated_letters(&mut self, no_repeated_letters: bool) {
+        self.no_repeated_letters = no_repeated_letters;
+    }
+
+    /// Set whether target words missing from the valid-word files are skipped, rather
+    /// than made valid.
+    pub fn set_strict_targets(&mut self, strict_targets: bool) {
+        self.strict_targets = strict_targets;
+    }
+
+    /// Returns true if only game starts, wins and losses are sent to the admin log.
+    pub fn quiet_admin(&self) -> bool {
+        self.quiet_admin
+    }
+
+    /// Set whether intermediate turns are left out of the admin log.
+    pub fn set_quiet_admin(&mut self, quiet_admin: bool) {
+        self.quiet_admin = quiet_admin;
+    }
+
+    /// Returns true if incoming updates are logged in full.
+    pub fn debug_updates(&self) -> bool {
+        self.debug_updates
+    }
+
+    /// Set whether incoming updates are logged in full. They may hold personal
+    /// data, so this is for debugging only.
+    pub fn set_debug_updates(&mut self, debug_updates: bool) {
+        self.debug_updates = debug_updates;
+    }
+
+    /// Returns true if new games are paused for maintenance.
+    pub async fn is_paused(&self) -> bool {
+        *self.paused.read().await
+    }
+
+    /// Pauses or resumes new games in every chat. Games in progress can still be
+    /// finished while paused.
+    pub async fn set_paused(&self, paused: bool) {
+        *self.paused.write().await = paused;
+    }
+
+    /// Returns true if there's an admin chat to send messages to.
+    pub async fn has_admin_chat(&self) -> bool {
+        self.admin_chat_id.read().await.is_some()
+    }
+
+    /// Forwards feedback from the user to the admin chat. Returns false if the user
+    /// sent feedback too recently.
+    pub async fn send_feedback(&self, api: Arc<API>, from: &User, text: &str) -> bool {
+        let now = Instant::now();
+        {
+            let mut feedback_times = self.feedback_times.write().await;
+            if let Some(last) = feedback_times.get(&from.id) {
+                if now.duration_since(*last) < FEEDBACK_INTERVAL {
+                    return false;
+                }
+            }
+            feedback_times.insert(from.id, now);
+        }
+
+        // Feedback is sent on purpose, so it's forwarded regardless of analytics.
+        self.send_to_admin(
+            api,
+            format!(
+                "Feedback from {} ({}, {}): {}",
+                from.first_name,
+                from.username.clone().unwrap_or_default(),
+                from.id,
+                text
+            ),
+        )
+        .await;
+        true
+    }
+
+    /// Sends a message to the admin chat, if there is one.
+    async fn send_to_admin(&self, api: Arc<API>, text: String) {
+        send_to_admin_chat(&self.admin_chat_id, api, text).await;
+    }
+
+    /// Returns true if the word is a valid word.
+    pub fn is_valid_word(&self, word: String) -> bool {
+        let words = self.words();
+        let word = word.to_lowercase();
+        words.valid.is_empty()
+            || words.valid.contains(&word)
+            || (self.accent_insensitive && words.folded.contains(&word))
+    }
+
+    /// Returns the number of valid words. If zero, words are not validated.
+    pub fn valid_word_count(&self) -> usize {
+        self.words().valid.len()
+    }
+
+    /// Returns a random valid word, if there are any.
+    pub fn random_valid_word(&self) -> Option<String> {
+        self.words()
+            .valid
+            .iter()
+            .choose(&mut rand::thread_rng())
+            .cloned()
+    }
+
+    /// Returns the words still possible in the current game, or None if words
+    /// aren't validated.
+    pub fn possible_words(&self) -> Option<Vec<String>> {
+        let words = self.words();
+        if words.solver_index.is_empty() {
+            return None;
+        }
+
+        Some(words.solver_index.filter(self.wordle.as_ref()?))
+    }
+
+    /// Has the solver play a game against the word with the valid words, or returns
+    /// None if words aren't validated.
+    pub fn solve(&self, word: &str) -> Result<Option<Solution>> {
+        let words = self.words();
+        if words.solver_index.is_empty() {
+            return Ok(None);
+        }
+
+        Ok(Some(solver::solve(
+            &words.solver_index,
+            word,
+            wordle::MAX_ATTEMPTS,
+        )?))
+    }
+
+    /// Set the valid words for this game, and index them for the solver. The target
+    /// words of the game and its modes are added to them.
+    pub fn set_valid_words(&mut self, valid_words: HashSet<String>) {
+        let targets = self.words().targets.clone();
+        *self.words.write().unwrap() = Arc::new(WordLists::new(
+            targets,
+            valid_words,
+            self.modes.values().flatten(),
+        ));
+    }
+
+    /// Set the files the word lists are read from, for /reload.
+    pub fn set_word_files(&mut self, target_words_file: String, valid_words_files: Vec<String>) {
+        self.target_words_file = target_words_file;
+        self.valid_words_files = valid_words_files;
+    }
+
+    /// Set the combined word file the target and valid-only words are read from, for
+    /// /reload. If set, it's read instead of the target words file.
+    pub fn set_combined_words_file(&mut self, combined_words_file: Option<String>) {
+        self.combined_words_file = combined_words_file;
+    }
+
+    /// Re-reads the target and valid words from their files, and swaps them in for
+    /// every chat. Games in progress keep their targets, even if they're no longer in
+    /// the lists. Returns the number of target and valid words.
+    pub async fn reload_words(&self) -> Result<(usize, usize)> {
+        let (mut targets, valid_only) = match &self.combined_words_file {
+            Some(path) => words::load_combined_words(path).await,
+            None => (words::load_words(&self.target_words_file).await, vec![]),
+        };
+        if targets.is_empty() {
+            bail!(
+                "no target words found in {}",
+                self.combined_words_file
+                    .as_ref()
+                    .unwrap_or(&self.target_words_file)
+            );
+        }
+        targets.shuffle(&mut rand::thread_rng());
+
+        let mut valid = words::load_valid_words(&self.valid_words_files, &[]).await;
+        valid.extend(valid_only);
+
+        let words = WordLists::new(targets, valid, self.modes.values().flatten());
+        let counts = (words.targets.len(), words.valid.len());
+        *self.words.write().unwrap() = Arc::new(words);
+        Ok(counts)
+    }
+
+    /// Set whether to avoid serving a target within one edit of the previous target,
+    /// e.g. HELLS right after HELLO.
+    pub fn set_avoid_similar_targets(&mut self, avoid_similar_targets: bool) {
+        self.avoid_similar_targets = avoid_similar_targets;
+    }
+
+    /// Returns how strictly players must use the hints from earlier attempts.
+    pub fn hard_mode(&self) -> wordle::HardMode {
+        self.hard_mode
+    }
+
+    /// Set how strictly players must use the hints from earlier attempts.
+    pub fn set_hard_mode(&mut self, hard_mode: wordle::HardMode) {
+        self.hard_mode = hard_mode;
+    }
+
+    /// Set whether guesses match letters regardless of accents, e.g. "CAFE" wins
+    /// against "CAFÉ".
+    pub fn set_accent_insensitive(&mut self, accent_insensitive: bool) {
+        self.accent_insensitive = accent_insensitive;
+    }
+
+    /// Returns true if the message that implicitly starts a game is also played
+    /// as the first guess.
+    pub fn first_message_counts(&self) -> bool {
+        self.first_message_counts
+    }
+
+    /// Set whether the message that implicitly starts a game is also played as the
+    /// first guess.
+    pub fn set_first_message_counts(&mut self, first_message_counts: bool) {
+        self.first_message_counts = first_message_counts;
+    }
+
+    /// Set the directory where game state is saved.
+    pub fn set_save_dir(&mut self, save_dir: String) {
+        self.save_dir = save_dir;
+    }
+
+    /// Set how long saves are held back so that multiple writes for the same user
+    /// coalesce into one. If zero, state is saved immediately.
+    pub fn set_save_delay(&mut self, save_delay: Duration) {
+        self.save_delay = save_delay;
+    }
+
+    /// Set the format user saves are written in. Saves in any format are read.
+    pub fn set_save_format(&mut self, save_format: SaveFormat) {
+        self.save_format = save_format;
+    }
+
+    /// Set how many chats' games are kept in memory. Past this, the games of the
+    /// least recently active chats are saved and dropped, and reloaded on the chat's
+    /// next message. The current chat's game is always kept, so a cap of 0 acts like
+    /// 1. If `None`, there's no cap. Games are only dropped with a save directory.
+    pub fn set_max_games(&mut self, max_games: Option<usize>) {
+        self.max_games = max_games;
+    }
+
+    /// Returns true if chats drop their game from memory after each message, which
+    /// they do when games are capped and saved. The game is kept in the tracked games
+    /// until it's evicted, and reloaded from the save on the chat's next message.
+    pub fn releases_games(&self) -> bool {
+        self.max_games.is_some() && !self.save_dir.is_empty()
+    }
+
+    /// Drops the game and the rest of the player's saved data from the chat's state.
+    pub fn release_game(&mut self) {
+        self.wordle = None;
+        self.last_finished = None;
+        self.played_words = HashSet::new();
+        self.recent = vec![];
+        self.settings = UserSettings::default();
+        self.won_words = HashSet::new();
+        self.released = true;
+    }
+
+    /// Returns true if the chat's game was released since it was last loaded, and
+    /// clears the flag.
+    pub fn take_released(&mut self) -> bool {
+        std::mem::take(&mut self.released)
+    }
+
+    /// Spawns a background task that periodically flushes pending saves. Does
+    /// nothing if saves are not delayed.
+    pub fn spawn_save_flusher(&self) {
+        if self.save_delay.is_zero() {
+            return;
+        }
+
+        let app = self.clone();
+        tokio::spawn(async move {
+            loop {
+                tokio::time::sleep(app.save_delay).await;
+                if let Err(e) = app.flush_saves().await {
+                    error!("Error flushing game state: {}", e);
+                }
+            }
+        });
+    }
+
+    /// Writes all pending saves to disk.
+    pub async fn flush_saves(&self) -> anyhow::Result<()> {
+        let pending = std::mem::take(&mut *self.pending_saves.write().await);
+        for save_data in pending.values() {
+            self.write_save(save_data).await?;
+        }
+
+        if !self.save_dir.is_empty() && !pending.is_empty() {
+            wordstats::save_word_stats(&self.save_dir, &*self.word_stats.read().await).await?;
+        }
+        Ok(())
+    }
+
+    /// Returns the user's current score
+    pub async fn score(&self, from: &String) -> Score {
+        self.scores
+            .read()
+            .await
+            .get(from)
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    /// Returns the user's score for the current season.
+    pub async fn season_score(&self, from: &String) -> Score {
+        self.season_scores
+            .read()
+            .await
+            .get(from)
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    /// Returns the current season.
+    pub async fn season(&self) -> Season {
+        self.season.read().await.clone()
+    }
+
+    /// Returns true if scores are also tracked per season.
+    pub fn has_seasons(&self) -> bool {
+        self.season_length.is_some()
+    }
+
+    /// Set the length of a season. If `None`, there are no seasons.
+    pub fn set_season_length(&mut self, season_length: Option<Duration>) {
+        self.season_length = season_length;
+    }
+
+    /// Set whether the top players of a season are announced to the admin chat when
+    /// it ends.
+    pub fn set_season_announce(&mut self, season_announce: bool) {
+        self.season_announce = season_announce;
+    }
+
+    /// Loads the current season from the save directory.
+    pub async fn load_season(&self) -> Result<()> {
+        if self.save_dir.is_empty() {
+            return Ok(());
+        }
+
+        let loaded = season::load_season(&self.save_dir).await;
+        if let Err(e) = &loaded {
+            warn!("Starting a new season: {}", e);
+            season::save_season(&self.save_dir, &*self.season.read().await).await?;
+        } else {
+            *self.season.write().await = loaded?;
+        }
+
+        info!("Playing season {}", self.season.read().await.number);
+        Ok(())
+    }
+
+    /// Ends the current season if it has run its course.
+    pub async fn maybe_end_season(&self, now: u64) -> Result<Option<SeasonArchive>> {
+        let Some(season_length) = self.season_length else {
+            return Ok(None);
+        };
+
+        if now < self.season.read().await.started_at + season_length.as_secs() {
+            return Ok(None);
+        }
+
+        Ok(Some(self.end_season(now).await?))
+    }
+
+    /// Archives the scores of the current season and starts a new one. All-time
+    /// scores are not affected.
+    pub async fn end_season(&self, now: u64) -> Result<SeasonArchive> {
+        let mut season = self.season.write().await;
+        let mut season_scores = self.season_scores.write().await;
+
+        // Scores in memory are the most recent, but users who haven't played since
+        // the bot started only have their scores on disk.
+        let mut scores = HashMap::new();
+        for save_data in self.read_all_saves().await {
+            if save_data.season == season.number {
+                scores.insert(save_data.user_id, save_data.season_score);
+            }
+        }
+        scores.extend(season_scores.drain());
+
+        let archive = SeasonArchive {
+            number: season.number,
+            started_at: season.started_at,
+            ended_at: now,
+            scores,
+        };
+
+        *season = Season {
+            number: season.number + 1,
+            started_at: now,
+        };
+
+        if !self.save_dir.is_empty() {
+            season::write_archive(&self.save_dir, &archive).await?;
+            season::save_season(&self.save_dir, &season).await?;
+        }
+
+        info!(
+            "Season {} ended, starting season {}",
+            archive.number, season.number
+        );
+        Ok(archive)
+    }
+
+    /// Ends the current season if it has run its course, and announces its top
+    /// players to the admin chat if enabled.
+    pub async fn rotate_season(&self, api: Arc<API>, now: u64) -> Result<()> {
+        let Some(archive) = self.maybe_end_season(now).await? else {
+            return Ok(());
+        };
+
+        if self.season_announce {
+            let summary = self.season_summary(&archive).await;
+            self.send_to_admin(api, summary).await;
+        }
+        Ok(())
+    }
+
+    /// Returns a summary of the top players of an archived season. Players are named
+    /// as on the leaderboard.
+    async fn season_summary(&self, archive: &SeasonArchive) -> String {
+        let names = self
+            .leaderboard()
+            .await
+            .unwrap_or_else(|e| {
+                warn!("Could not read player names: {}", e);
+                vec![]
+            })
+            .into_iter()
+            .map(|e| (e.user_id, e.name))
+            .collect::<HashMap<_, _>>();
+
+        let mut entries = archive
+            .scores
+            .iter()
+            .map(|(user_id, score)| leaderboard::Entry {
+                user_id: user_id.clone(),
+                name: names.get(user_id).unwrap_or(user_id).clone(),
+                handle: String::new(),
+                score: score.clone(),
+            })
+            .collect::<Vec<_>>();
+        leaderboard::rank(&mut entries);
+
+        if entries.is_empty() {
+            return format!("Season {} is over! Nobody played.", archive.number);
+        }
+
+        let mut summary = format!("Season {} is over! Top players:", archive.number);
+        for (i, entry) in entries.iter().take(SEASON_ANNOUNCE_SIZE).enumerate() {
+            summary.push_str(&format!(
+                "\n{}. {} {}",
+                i + 1,
+                entry.name,
+                format_score(&entry.score, self.min_games_for_rate)
+            ));
+        }
+        summary
+    }
+
+    /// Spawns a background task that ends seasons as they run their course. Does
+    /// nothing if there are no seasons.
+    pub fn spawn_season_rotation(&self, api: Arc<API>) {
+        if self.season_length.is_none() {
+            return;
+        }
+
+        let app = self.clone();
+        tokio::spawn(async move {
+            loop {
+                if let Err(e) = app.rotate_season(Arc::clone(&api), season::now()).await {
+                    error!("Error ending season: {}", e);
+                }
+                tokio::time::sleep(Duration::from_secs(60)).await;
+            }
+        });
+    }
+
+    /// Records the player's names, for players the leaderboard hasn't scanned yet.
+    async fn remember_player(&self, user: &User) {
+        self.players
+            .write()
+            .await
+            .insert(user.id.to_string(), user.clone());
+    }
+
+    /// Returns the ranked all-time scores of every player. Save files are scanned
+    /// at most once every LEADERBOARD_TTL, and scores of players loaded since then
+    /// are brought up to date from memory.
+    pub async fn leaderboard(&self) -> Result<Vec<leaderboard::Entry>> {
+        let cached = self
+            .leaderboard
+            .read()
             .await
-            .context(format!("Error reading file {}", filename))?;
+            .as_ref()
+            .filter(|(scanned_at, _)| scanned_at.elapsed() < LEADERBOARD_TTL)
+            .map(|(_, entries)| entries.clone());
+
+        let mut entries = match cached {
+            Some(entries) => entries,
+            None if self.save_dir.is_empty() => vec![],
+            None => {
+                let entries =
+                    leaderboard::scan(&self.save_dir, leaderboard::SCAN_CONCURRENCY).await?;
+                *self.leaderboard.write().await = Some((Instant::now(), entries.clone()));
+                entries
+            }
+        };
+
+        let players = self.players.read().await;
+        for (user_id, score) in self.scores.read().await.iter() {
+            match entries.iter_mut().find(|e| &e.user_id == user_id) {
+                Some(entry) => entry.score = score.clone(),
+                None => {
+                    let player = players.get(user_id);
+                    entries.push(leaderboard::Entry::new(
+                        user_id.clone(),
+                        player.and_then(|p| p.username.clone()).unwrap_or_default(),
+                        player.map(|p| p.first_name.as_str()).unwrap_or_default(),
+                        score.clone(),
+                    ))
+                }
+            }
+        }
+
+        leaderboard::rank(&mut entries);
+        Ok(entries)
+    }
+
+    /// Loads the word stats from the save directory.
+    pub async fn load_word_stats(&self) -> Result<()> {
+        if self.save_dir.is_empty() {
+            return Ok(());
+        }
+
+        *self.word_stats.write().await = wordstats::load_word_stats(&self.save_dir).await?;
+        Ok(())
+    }
+
+    /// Returns the stats of the given target word, if it has been served.
+    pub async fn word_stats(&self, word: &str) -> Option<WordStats> {
+        self.word_stats
+            .read()
+            .await
+            .get(&word.to_uppercase())
+            .cloned()
+    }
+
+    /// Updates the stats of the given target word, and saves them. With a save
+    /// delay, they're saved along with the pending user saves.
+    async fn update_word_stats(&self, word: &str, update: impl FnOnce(&mut WordStats)) {
+        let mut stats = self.word_stats.write().await;
+        update(stats.entry(word.to_uppercase()).or_default());
+
+        if self.save_dir.is_empty() || !self.save_delay.is_zero() {
+            return;
+        }
+        if let Err(e) = wordstats::save_word_stats(&self.save_dir, &stats).await {
+            error!("Error saving word stats: {}", e);
+        }
+    }
+
+    /// Returns the numbers of all archived seasons.
+    pub async fn archived_seasons(&self) -> Result<Vec<u32>> {
+        if self.save_dir.is_empty() {
+            return Ok(vec![]);
+        }
+
+        season::list_archives(&self.save_dir).await
+    }
+
+    /// Increments the number of games this user played and saves state.
+    pub async fn inc_games(&self, from: &User) {
+        self.scores
+            .write()
+            .await
+            .entry(from.id.to_string())
+            .or_default()
+            .games += 1;
+        self.season_scores
+            .write()
+            .await
+            .entry(from.id.to_string())
+            .or_default()
+            .games += 1;
+        if let Err(e) = self.save(from).await {
+            error!("Error saving game state: {}", e);
+        }
+    }
+
+    /// Takes back the game counted for this user when it started. The caller saves
+    /// state.
+    async fn uncount_game(&self, from: &User) {
+        for scores in [&self.scores, &self.season_scores] {
+            let mut scores = scores.write().await;
+            let score = scores.entry(from.id.to_string()).or_default();
+            score.games = score.games.saturating_sub(1);
+        }
+    }
+
+    /// Overwrites the all-time score of the user with the given ID, and saves it.
+    /// Users without saved state get a new save.
+    pub async fn set_score(&self, user_id: &str, wins: u32, games: u32) -> Result<Score> {
+        if wins > games {
+            bail!("wins can't be more than games");
+        }
+        if self.save_dir.is_empty() {
+            bail!("no save directory is configured");
+        }
+
+        // Players without a save yet get one.
+        let pending = self.pending_saves.read().await.get(user_id).cloned();
+        let mut save_data = match pending {
+            Some(save_data) => save_data,
+            None if self.find_save(user_id).await.is_none() => {
+                SaveData::new(user_id, self.season.read().await.number)
+            }
+            None => migrate(self.read_save(user_id, false).await?),
+        };
+
+        // The new score replaces the old one, so earlier forgiven losses can't leave
+        // more wins than counted games.
+        save_data.score = Score {
+            games,
+            wins,
+            forgiven: 0,
+        };
+        self.scores
+            .write()
+            .await
+            .insert(user_id.to_string(), save_data.score.clone());
+
+        let score = save_data.score.clone();
+        if self.save_delay.is_zero() {
+            self.write_save(&save_data).await?;
+        } else {
+            self.pending_saves
+                .write()
+                .await
+                .insert(user_id.to_string(), save_data);
+        }
+
+        Ok(score)
+    }
+
+    /// Forgives a loss for this user, so it doesn't count against their win rate.
+    /// The caller saves state.
+    async fn inc_forgiven(&self, from: &User) {
+        self.scores
+            .write()
+            .await
+            .entry(from.id.to_string())
+            .or_default()
+            .forgiven += 1;
+        self.season_scores
+            .write()
+            .await
+            .entry(from.id.to_string())
+            .or_default()
+            .forgiven += 1;
+    }
+
+    /// Returns true if a loss to the word is forgiven because the word is rare.
+    pub fn is_forgiven(&self, word: &str) -> bool {
+        match self.mercy_frequency {
+            Some(threshold) => self
+                .word_frequencies
+                .get(&word.to_uppercase())
+                .is_some_and(|frequency| *frequency < threshold),
+            None => false,
+        }
+    }
+
+    /// Set the word frequencies, and the frequency below which losses are forgiven.
+    /// If `mercy_frequency` is None, no losses are forgiven.
+    pub fn set_mercy(
+        &mut self,
+        word_frequencies: HashMap<String, u64>,
+        mercy_frequency: Option<u64>,
+    ) {
+        self.word_frequencies = Arc::new(word_frequencies);
+        self.mercy_frequency = mercy_frequency;
+    }
+
+    /// Increments the number of wins for this user. The caller saves state.
+    pub async fn inc_wins(&mut self, from: &User) {
+        self.scores
+            .write()
+            .await
+            .entry(from.id.to_string())
+            .or_default()
+            .wins += 1;
+        self.season_scores
+            .write()
+            .await
+            .entry(from.id.to_string())
+            .or_default()
+            .wins += 1;
+        self.won_words
+            .insert(self.wordle.as_ref().unwrap().target_word.clone());
+    }
+
+    /// Returns the results of the user's last RECENT_GAMES games, oldest first.
+    pub fn recent_games(&self) -> &[GameResult] {
+        &self.recent
+    }
+
+    /// Returns the most recent game the user finished, if any.
+    pub fn last_finished(&self) -> Option<&Wordle> {
+        self.last_finished.as_ref()
+    }
+
+    /// Plays a turn for the user with the given word, and returns the move along with
+    /// the board, target and score after it.
+    pub async fn play_turn(&mut self, from: &User, word: String) -> anyhow::Result<TurnResult> {
+        let move_ = self.play_move(from, word).await?;
+        let wordle = self.wordle.as_ref().unwrap();
+        let board = wordle.game()?;
+
+        // With the assist, the guess that uses up half the attempts reveals a letter.
+        let reveal = if self.settings.assist
+            && matches!(move_, Move::Valid)
+            && board.attempts.len() == board.max_attempts / 2
+        {
+            unsolved_letter(&board, &wordle.target_word)
+        } else {
+            None
+        };
+
+        Ok(TurnResult {
+            move_,
+            board,
+            target: wordle.target_word.clone(),
+            score: self.score(&from.id.to_string()).await,
+            reveal,
+        })
+    }
+
+    /// Plays the word like `play_turn`, but only if the chat is still playing the game
+    /// identified by `game_serial`. Checking and playing under one borrow keeps a
+    /// guess from being played against a game that started after it arrived.
+    pub async fn play_turn_in(
+        &mut self,
+        game_serial: u64,
+        from: &User,
+        word: String,
+    ) -> anyhow::Result<TurnResult> {
+        if game_serial == self.game_serial {
+            return self.play_turn(from, word).await;
+        }
+
+        let wordle = self.wordle.as_ref().ok_or(anyhow!("no game in progress"))?;
+        Ok(TurnResult {
+            move_: Move::GameChanged,
+            board: wordle.game()?,
+            target: wordle.target_word.clone(),
+            score: self.score(&from.id.to_string()).await,
+            reveal: None,
+        })
+    }
+
+    /// Plays the word in the current game, and updates the user's score.
+    async fn play_move(&mut self, from: &User, word: String) -> anyhow::Result<Move> {
+        // Emoji and symbols can't be assessed, even if they happen to fit.
+        if !word.chars().all(char::is_alphabetic) {
+            return Ok(Move::NotLetters);
+        }
+
+        if self.no_repeated_letters && has_repeated_letters(&word) {
+            return Ok(Move::RepeatedLetters);
+        }
+
+        // The target is always a valid guess, even if a reload dropped it from the lists.
+        let is_target = self.wordle.as_ref().is_some_and(|w| w.is_target(&word));
+        if !is_target && !self.is_valid_word(word.clone()) {
+            return Ok(Move::InvalidWord);
+        }
+
+        if !self.wordle.as_ref().unwrap().fits(&word) {
+            return Ok(Move::InvalidLength);
+        }
+
+        if self.hard_mode != wordle::HardMode::Off {
+            let after_first_green = self.hard_mode == wordle::HardMode::AfterGreen;
+            if let Err(e) = self
+                .wordle
+                .as_ref()
+                .unwrap()
+                .validate_hard_mode(&word, after_first_green)
+            {
+                return Ok(Move::BrokeHardMode(e.to_string()));
+            }
+        }
+
+        let game = self.wordle.as_mut().unwrap().play_turn(word)?;
+        if game.state != wordle::State::Playing {
+            self.finish_game(&game).await;
+        }
+
+        let move_ = match game.state {
+            wordle::State::Won => {
+                self.last_win = Some(Instant::now());
+                if !self.is_practice() {
+                    self.inc_wins(from).await;
+                }
+                Move::Won
+            }
+            wordle::State::Lost => {
+                let target_word = self.wordle.as_ref().unwrap().target_word.clone();
+                if !self.is_practice() && self.is_forgiven(&target_word) {
+                    self.inc_forgiven(from).await;
+                }
+                Move::Lost
+            }
+            _ => Move::Valid,
+        };
+
+        // Save every accepted attempt along with its result, so a restart mid-game
+        // doesn't lose progress. With a save delay, the writes coalesce like any other
+        // save.
+        if let Err(e) = self.save(from).await {
+            error!("Error saving game state: {}", e);
+        }
+        Ok(move_)
+    }
 
-        let save_data: SaveData = serde_json::from_slice(&contents)
-            .context(format!("Error deserializing game state from {}", filename))?;
+    /// Gives up the current game, which counts as a loss unless give-ups don't count,
+    /// and returns the move along with the board, target and score after it.
+    pub async fn give_up(&mut self, from: &User) -> anyhow::Result<TurnResult> {
+        let wordle = self.wordle.as_mut().ok_or(anyhow!("no game in progress"))?;
+        let game = wordle.give_up()?;
+        let target_word = wordle.target_word.clone();
 
-        self.won_words = HashSet::from_iter(save_data.won_words.clone());
-        if self.played_words.len() < self.won_words.len() {
-            self.played_words = HashSet::from_iter(save_data.won_words);
+        if self.is_practice() || self.giveup_counts {
+            // Practice games were never counted, and finish_game skips them.
+            self.finish_game(&game).await;
+            if !self.is_practice() && self.is_forgiven(&target_word) {
+                self.inc_forgiven(from).await;
+            }
         } else {
-            self.played_words = HashSet::from_iter(save_data.played_words);
+            // The game is taken back, so it isn't recorded as a loss anywhere.
+            self.last_finished = self.wordle.clone();
+            self.uncount_game(from).await;
+        }
+
+        if let Err(e) = self.save(from).await {
+            error!("Error saving game state: {}", e);
+        }
+
+        Ok(TurnResult {
+            move_: Move::Lost,
+            board: game,
+            target: target_word,
+            score: self.score(&from.id.to_string()).await,
+            reveal: None,
+        })
+    }
+
+    /// Records the result of the game that just ended in the recent games and the
+    /// word stats, unless it was a practice game.
+    async fn finish_game(&mut self, game: &wordle::Game) {
+        let target_word = self.wordle.as_ref().unwrap().target_word.clone();
+        let won = game.state == wordle::State::Won;
+        let guesses = game.attempts.len();
+
+        self.last_finished = self.wordle.clone();
+        if self.is_practice() {
+            return;
+        }
+
+        self.recent.push(GameResult {
+            target: target_word.clone(),
+            won,
+            guesses,
+            max_attempts: game.max_attempts,
+        });
+        if self.recent.len() > RECENT_GAMES {
+            self.recent.remove(0);
+        }
+
+        self.update_word_stats(&target_word, |stats| {
+            if won {
+                stats.wins += 1;
+                stats.winning_guesses += guesses as u32;
+            } else {
+                stats.losses += 1;
+            }
+        })
+        .await;
+    }
+
+    /// Returns the played words to save, capped to the number of target words, since
+    /// they only serve to avoid repeats within a cycle through the targets. Words no
+    /// longer in the target lists are dropped first. If every target has been
+    /// played, the cycle starts over from the current target.
+    fn pruned_played_words(&self) -> Vec<String> {
+        let targets = self
+            .words()
+            .targets
+            .iter()
+            .chain(self.modes.values().flatten())
+            .map(|w| w.to_uppercase())
+            .collect::<HashSet<_>>();
+
+        if self.played_words.len() <= targets.len() {
+            return self.played_words.iter().cloned().collect();
+        }
+
+        let played = self
+            .played_words
+            .iter()
+            .filter(|w| targets.contains(*w))
+            .cloned()
+            .collect::<Vec<_>>();
+        if played.len() < targets.len() {
+            return played;
+        }
+
+        self.wordle.iter().map(|w| w.target_word.clone()).collect()
+    }
+
+    /// Save game state for user. If a save delay is configured, the state is
+    /// marked dirty and written by the next flush instead.
+    pub async fn save(&self, user: &User) -> anyhow::Result<()> {
+        self.remember_player(user).await;
+        if self.save_dir.is_empty() {
+            return Ok(());
+        }
+
+        let last_wordle = self.wordle.clone();
+
+        let save_data = SaveData {
+            version: SAVE_VERSION,
+            user_id: user.id.clone().to_string(),
+            user_handle: user.username.clone().unwrap_or_default(),
+            user_first_name: user.first_name.clone(),
+            user_last_name: user.last_name.clone().unwrap_or_default(),
+            played_words: self.pruned_played_words(),
+            won_words: self.won_words.iter().cloned().collect(),
+            score: self.score(&user.id.to_string()).await,
+            season: self.season.read().await.number,
+            season_score: self.season_score(&user.id.to_string()).await,
+            last_wordle,
+            last_finished: self.last_finished.clone(),
+            recent: self.recent.clone(),
+            settings: self.settings.clone(),
+            legacy_settings: UserSettings::default(),
+        };
+
+        if !self.save_delay.is_zero() {
+            self.pending_saves
+                .write()
+                .await
+                .insert(save_data.user_id.clone(), save_data);
+            return Ok(());
+        }
+
+        self.write_save(&save_data).await
+    }
+
+    /// Writes the save data to the user's save file, in the save format. The save is
+    /// written to a temporary file first and renamed into place, so a crash mid-write
+    /// can't leave a truncated save. A save in another format is removed, so it can't
+    /// shadow the new one.
+    async fn write_save(&self, save_data: &SaveData) -> anyhow::Result<()> {
+        let filename = self.save_filename(&save_data.user_id, self.save_format);
+        let tmp_filename = format!("{}.tmp", filename);
+
+        let mut file = File::create(tmp_filename.clone())
+            .await
+            .context(format!("Error creating file {}", tmp_filename))?;
+
+        file.write_all(
+            self.save_format
+                .serialize(save_data)
+                .context("Error serializing game state")?
+                .as_ref(),
+        )
+        .await
+        .context(format!("Error writing file {}", tmp_filename))?;
+
+        // Make sure the write has landed before the file is renamed and read back.
+        file.flush()
+            .await
+            .context(format!("Error writing file {}", tmp_filename))?;
+        tokio::fs::rename(&tmp_filename, &filename)
+            .await
+            .context(format!("Error moving {} to {}", tmp_filename, filename))?;
+
+        for format in SaveFormat::ALL
+            .into_iter()
+            .filter(|f| *f != self.save_format)
+        {
+            let stale = self.save_filename(&save_data.user_id, format);
+            if let Err(e) = tokio::fs::remove_file(&stale).await {
+                if e.kind() != std::io::ErrorKind::NotFound {
+                    warn!("Error removing old save file {}: {}", stale, e);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the name of the user's save file in the given format.
+    fn save_filename(&self, user_id: &str, format: SaveFormat) -> String {
+        format!("{}/{}.{}", self.save_dir, user_id, format.extension())
+    }
+
+    /// Load game state for user.
+    pub async fn load(&mut self, user: &User) -> anyhow::Result<()> {
+        self.remember_player(user).await;
+        if self.save_dir.is_empty() {
+            bail!("No save directory configured");
         }
+
+        // A pending save is newer than whatever is on disk.
+        let pending = self
+            .pending_saves
+            .read()
+            .await
+            .get(&user.id.to_string())
+            .cloned();
+
+        let save_data = match pending {
+            Some(save_data) => save_data,
+            None => migrate(self.read_save(&user.id.to_string(), true).await?),
+        };
+
+        self.won_words = HashSet::from_iter(save_data.won_words);
+        self.played_words = HashSet::from_iter(save_data.played_words);
         self.scores
             .write()
             .await
             .insert(user.id.to_string(), save_data.score);
+
+        // Season scores from earlier seasons start over.
+        let season_score = if save_data.season == self.season.read().await.number {
+            save_data.season_score
+        } else {
+            Score::default()
+        };
+        self.season_scores
+            .write()
+            .await
+            .insert(user.id.to_string(), season_score);
+        self.settings = save_data.settings;
+        self.recent = save_data.recent;
         self.wordle = save_data.last_wordle;
 
+        // Saves from before `last_finished` may still hold a finished game.
+        self.last_finished = save_data.last_finished.or_else(|| {
+            self.wordle
+                .clone()
+                .filter(|w| w.game().is_ok_and(|g| g.state != wordle::State::Playing))
+        });
+
         Ok(())
     }
+
+    /// Returns the format of the user's save file, or `None` if they have no save. A
+    /// save in the save format is preferred.
+    async fn find_save(&self, user_id: &str) -> Option<SaveFormat> {
+        let others = SaveFormat::ALL
+            .into_iter()
+            .filter(|f| *f != self.save_format);
+        for format in std::iter::once(self.save_format).chain(others) {
+            let filename = self.save_filename(user_id, format);
+            if tokio::fs::try_exists(&filename).await.unwrap_or(false) {
+                return Some(format);
+            }
+        }
+        None
+    }
+
+    /// Reads the save data from the user's save file, in whichever format it was
+    /// saved. A save in the save format is preferred. With `move_corrupt`, a save
+    /// that can't be deserialized is renamed to end in `.corrupt`, so it can be
+    /// recovered by hand and the user starts over instead of the save being
+    /// overwritten. Only the user's own load moves saves, not scans of every save.
+    async fn read_save(&self, user_id: &str, move_corrupt: bool) -> anyhow::Result<SaveData> {
+        let format = self.find_save(user_id).await.unwrap_or(self.save_format);
+        let filename = self.save_filename(user_id, format);
+
+        let mut file = File::open(filename.clone())
+            .await
+            .context(format!("Error opening file {}", filename))?;
+
+        let mut contents = vec![];
+        file.read_to_end(&mut contents)
+            .await
+            .context(format!("Error reading file {}", filename))?;
+
+        let save_data = format.parse(&contents);
+        match &save_data {
+            Err(e) if move_corrupt => {
+                let corrupt = format!("{}.corrupt", filename);
+                error!(
+                    "Corrupt save file {}, moving it to {}: {:#}",
+                    filename, corrupt, e
+                );
+                if let Err(e) = tokio::fs::rename(&filename, &corrupt).await {
+                    error!("Error moving corrupt save file {}: {}", filename, e);
+                }
+            }
+            _ => {}
+        }
+        save_data.context(format!("Error deserializing game state from {}", filename))
+    }
+
+    /// Reads the save data of every user in the save directory, skipping files
+    /// that can't be read.
+    async fn read_all_saves(&self) -> Vec<SaveData> {
+        let mut saves = vec![];
+        let Some(mut entries) = tokio::fs::read_dir(&self.save_dir).await.ok() else {
+            return saves;
+        };
+
+        while let Some(entry) = entries.next_entry().await.ok().flatten() {
+            let name = entry.file_name().to_string_lossy().to_string();
+            let Some(user_id) = saveformat::save_user_id(&name) else {
+                continue;
+            };
+
+            let save_data = self.read_save(user_id, false).await;
+            if let Err(e) = &save_data {
+                warn!("Skipping save file {}: {}", name, e);
+            }
+            saves.extend(save_data.ok().map(migrate));
+        }
+
+        saves
+    }
 }
@@ -1,6 +1,86 @@
-use crate::{app::App, handlers::handle_chat_event};
+use std::{sync::Arc, time::Duration};
+
+use crate::{
+    app::{format_percentage, format_score, App, Move, Score, RECENT_GAMES},
+    challenge,
+    handlers::{handle_bot_command, handle_chat_event, handle_edited_message, render_game},
+    saveformat::SaveFormat,
+};
 use log::*;
 use mobot::*;
+use tokio::sync::{mpsc, Notify};
+
+/// Starts a bot with the given app state and the same routes as `main`, wired to a
+/// fake Telegram server. Returns the fake server and the router's shutdown channels.
+fn start_bot(app: App) -> (fake::FakeAPI, Arc<Notify>, Arc<mpsc::Sender<()>>) {
+    let fakeserver = fake::FakeAPI::new();
+    let client = Client::new("token".to_string().into()).with_post_handler(fakeserver.clone());
+
+    let mut router = Router::new(client).with_state(app).with_poll_timeout_s(1);
+    router
+        .add_route(
+            Route::Message(Matcher::Prefix("/".into())),
+            handle_bot_command,
+        )
+        .add_route(Route::Message(Matcher::Any), handle_chat_event)
+        // Routes only see messages with text, unless they match photos or files.
+        // Those get a reply asking for text.
+        .add_route(Route::Message(Matcher::Photo), handle_chat_event)
+        .add_route(Route::Message(Matcher::Document), handle_chat_event)
+        .add_route(Route::EditedMessage(Matcher::Any), handle_edited_message);
+
+    let (shutdown_notifier, shutdown_tx) = router.shutdown();
+    tokio::spawn(async move {
+        router.start().await;
+    });
+
+    (fakeserver, shutdown_notifier, shutdown_tx)
+}
+
+async fn stop_bot(shutdown_notifier: Arc<Notify>, shutdown_tx: Arc<mpsc::Sender<()>>) {
+    shutdown_tx.send(()).await.unwrap();
+    shutdown_notifier.notified().await;
+}
+
+/// Returns a fresh, empty save directory for the named test.
+fn test_save_dir(name: &str) -> String {
+    let dir = std::env::temp_dir().join(format!("wordlebot-{}", name));
+    _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    dir.to_string_lossy().into()
+}
+
+fn test_user(id: i64) -> api::User {
+    api::User {
+        id,
+        first_name: "qubyte".into(),
+        username: Some("qubyte".into()),
+        ..Default::default()
+    }
+}
+
+/// Sends a text message to the chat from a player with the given ID. The fake
+/// chats always send as user 1, so tests with several players use this to tell
+/// them apart. The player's handle and first name are the chat's `from`.
+async fn send_as(chat: &fake::FakeChat, user_id: i64, text: &str) {
+    chat.send_update(Update::Message(api::Message {
+        from: Some(api::User {
+            id: user_id,
+            first_name: chat.from.clone(),
+            username: Some(chat.from.clone()),
+            ..Default::default()
+        }),
+        chat: api::Chat {
+            id: chat.chat_id,
+            chat_type: "private".into(),
+            ..Default::default()
+        },
+        text: Some(text.into()),
+        ..Default::default()
+    }))
+    .await
+    .unwrap();
+}
 
 /// This is an end-to-end test that starts the bot with just one target word ("hello"). It then
 /// starts two chats with the bot, and has them play the game. The first chat should win right
@@ -89,3 +169,2376 @@ async fn it_works() {
     shutdown_tx.send(()).await.unwrap();
     shutdown_notifier.notified().await;
 }
+
+/// With a save delay, rapid score changes are coalesced in memory and written once on
+/// flush with the final values.
+#[tokio::test]
+async fn save_delay_coalesces_writes() {
+    let save_dir = test_save_dir("save-delay");
+    let mut app = App::new("BadWordle".into(), vec!["hello".to_string()]);
+    app.set_save_dir(save_dir.clone());
+    app.set_save_delay(Duration::from_secs(60));
+
+    let user = test_user(42);
+    app.start_game().await.unwrap();
+    for _ in 0..3 {
+        app.inc_games(&user).await;
+    }
+
+    // Nothing has been written yet, but the pending state is visible to loads.
+    let filename = format!("{}/42.json", save_dir);
+    assert!(!std::path::Path::new(&filename).exists());
+    app.load(&user).await.unwrap();
+    assert_eq!(app.score(&"42".to_string()).await.games, 3);
+
+    app.flush_saves().await.unwrap();
+    let save_data: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(&filename).unwrap()).unwrap();
+    assert_eq!(save_data["score"]["games"], 3);
+
+    // The only other file is the word stats, written with the pending saves.
+    assert_eq!(std::fs::read_dir(&save_dir).unwrap().count(), 2);
+    assert!(std::path::Path::new(&format!("{}/word-stats.json", save_dir)).exists());
+}
+
+/// With `first_message_counts`, the message that starts the game is also assessed
+/// as the first guess.
+#[tokio::test]
+async fn first_message_counts() {
+    let mut app = App::new("BadWordle".into(), vec!["hello".to_string()]);
+    app.set_first_message_counts(true);
+    let (fakeserver, shutdown_notifier, shutdown_tx) = start_bot(app);
+
+    let chat = fakeserver.create_chat("qubyte").await;
+    chat.send_text("bolle").await.unwrap();
+    let reply = chat.recv_update().await.unwrap().to_string();
+    assert!(reply.starts_with("Hi qubyte, Welcome to BadWordle"));
+    assert!(reply.contains("Your attempts"));
+
+    // The game is already underway, so the next guess can win.
+    chat.send_text("hello").await.unwrap();
+    assert!(chat
+        .recv_update()
+        .await
+        .unwrap()
+        .to_string()
+        .contains("You won"));
+
+    stop_bot(shutdown_notifier, shutdown_tx).await;
+}
+
+/// `/length` changes the target word length of the player's next game.
+#[tokio::test]
+async fn length_command() {
+    let app = App::new(
+        "BadWordle".into(),
+        vec!["hello".to_string(), "banana".to_string()],
+    );
+    let (fakeserver, shutdown_notifier, shutdown_tx) = start_bot(app);
+
+    let chat = fakeserver.create_chat("qubyte").await;
+    chat.send_text("/length 7").await.unwrap();
+    assert!(chat
+        .recv_update()
+        .await
+        .unwrap()
+        .to_string()
+        .starts_with("Sorry, no 7-letter words available."));
+
+    chat.send_text("/length 6").await.unwrap();
+    assert_eq!(
+        chat.recv_update().await.unwrap().to_string(),
+        "Your next game will use a 6-letter word."
+    );
+
+    chat.send_text("/new").await.unwrap();
+    assert!(chat
+        .recv_update()
+        .await
+        .unwrap()
+        .to_string()
+        .ends_with("Guess the 6-letter word."));
+
+    stop_bot(shutdown_notifier, shutdown_tx).await;
+}
+
+/// `/tries` changes the number of guesses the player gets in their next games.
+#[tokio::test]
+async fn tries_command() {
+    let app = App::new("BadWordle".into(), vec!["hello".to_string()]);
+    let (fakeserver, shutdown_notifier, shutdown_tx) = start_bot(app);
+
+    let chat = fakeserver.create_chat("qubyte").await;
+    chat.send_text("/tries 11").await.unwrap();
+    assert_eq!(
+        chat.recv_update().await.unwrap().to_string(),
+        "Sorry, the number of guesses must be between 3 and 10."
+    );
+
+    chat.send_text("/tries 4").await.unwrap();
+    assert_eq!(
+        chat.recv_update().await.unwrap().to_string(),
+        "Your next game will give you 4 guesses."
+    );
+
+    chat.send_text("/new").await.unwrap();
+    chat.recv_update().await.unwrap();
+    for _ in 0..3 {
+        chat.send_text("bello").await.unwrap();
+        assert!(chat
+            .recv_update()
+            .await
+            .unwrap()
+            .to_string()
+            .contains("Your attempts"));
+    }
+
+    chat.send_text("bello").await.unwrap();
+    assert!(chat
+        .recv_update()
+        .await
+        .unwrap()
+        .to_string()
+        .contains("You lost"));
+
+    stop_bot(shutdown_notifier, shutdown_tx).await;
+}
+
+/// A word sent right after `/score` shows a finished game starts a new game, and the
+/// reply says it wasn't played as a guess.
+#[tokio::test]
+async fn word_after_score() {
+    let mut app = App::new("BadWordle".into(), vec!["hello".to_string()]);
+    app.set_save_dir(test_save_dir("word-after-score"));
+    let (fakeserver, shutdown_notifier, shutdown_tx) = start_bot(app);
+
+    let chat = fakeserver.create_chat("qubyte").await;
+    chat.send_text("hi").await.unwrap();
+    chat.recv_update().await.unwrap();
+    chat.send_text("hello").await.unwrap();
+    chat.recv_update().await.unwrap();
+
+    chat.send_text("/score").await.unwrap();
+    chat.recv_update().await.unwrap();
+    chat.send_text("bello").await.unwrap();
+    let reply = chat.recv_update().await.unwrap().to_string();
+    assert!(
+        reply.starts_with("Hi qubyte, Welcome to BadWordle!"),
+        "{}",
+        reply
+    );
+    assert!(
+        reply.ends_with("Your last game was over, so that started a new one. Send your guess now."),
+        "{}",
+        reply
+    );
+
+    // Later words are guesses as usual.
+    chat.send_text("bello").await.unwrap();
+    assert!(chat
+        .recv_update()
+        .await
+        .unwrap()
+        .to_string()
+        .contains("Your attempts"));
+
+    stop_bot(shutdown_notifier, shutdown_tx).await;
+}
+
+/// A saved length preference that no target matches is reported instead of falling
+/// back to a word of the wrong length.
+#[tokio::test]
+async fn start_game_without_matching_length() {
+    let save_dir = test_save_dir("no-matching-length");
+    std::fs::write(
+        format!("{}/42.json", save_dir),
+        r#"{"user_id": "42", "word_length": 6, "score": {"games": 0, "wins": 0}, "last_wordle": null}"#,
+    )
+    .unwrap();
+
+    let mut app = App::new("BadWordle".into(), vec!["hello".to_string()]);
+    app.set_save_dir(save_dir);
+    app.load(&test_user(42)).await.unwrap();
+
+    let err = app.start_game().await.unwrap_err();
+    assert_eq!(err.to_string(), "no words of that length available");
+    assert!(app.wordle.is_none());
+}
+
+/// Unversioned (version 1) saves are migrated to the current format on load.
+#[tokio::test]
+async fn load_migrates_version_1_saves() {
+    let save_dir = test_save_dir("migrate-v1");
+    let filename = format!("{}/42.json", save_dir);
+    std::fs::write(
+        &filename,
+        r#"{"user_id": "42", "won_words": ["HELLO"], "score": {"games": 1, "wins": 1}, "last_wordle": null}"#,
+    )
+    .unwrap();
+
+    let mut app = App::new(
+        "BadWordle".into(),
+        vec!["hello".to_string(), "world".to_string()],
+    );
+    app.set_save_dir(save_dir);
+    let user = test_user(42);
+    app.load(&user).await.unwrap();
+
+    // The won word counts as played, so it's not served again.
+    assert_eq!(app.start_game().await.unwrap(), "WORLD");
+
+    app.save(&user).await.unwrap();
+    let save_data: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(&filename).unwrap()).unwrap();
+    assert_eq!(save_data["version"], 3);
+    assert!(save_data["played_words"]
+        .as_array()
+        .unwrap()
+        .contains(&"HELLO".into()));
+}
+
+/// Version 2 saves kept settings as top-level fields, which are moved into the
+/// settings object. Saves with no settings at all get the defaults.
+#[tokio::test]
+async fn load_migrates_version_2_settings() {
+    let save_dir = test_save_dir("migrate-v2");
+    std::fs::write(
+        format!("{}/42.json", save_dir),
+        r#"{"version": 2, "user_id": "42", "score": {"games": 1, "wins": 1}, "last_wordle": null, "polite": true, "spoilers": false, "max_attempts": 4}"#,
+    )
+    .unwrap();
+    std::fs::write(
+        format!("{}/43.json", save_dir),
+        r#"{"version": 3, "user_id": "43", "score": {"games": 1, "wins": 1}, "last_wordle": null}"#,
+    )
+    .unwrap();
+
+    let mut app = App::new("BadWordle".into(), vec!["hello".to_string()]);
+    app.set_save_dir(save_dir.clone());
+    app.load(&test_user(42)).await.unwrap();
+    assert!(app.polite());
+    assert!(!app.spoilers());
+    assert_eq!(app.max_attempts(), 4);
+
+    app.save(&test_user(42)).await.unwrap();
+    let save_data: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(format!("{}/42.json", save_dir)).unwrap())
+            .unwrap();
+    assert_eq!(save_data["settings"]["polite"], true);
+    assert!(save_data.get("polite").is_none());
+
+    app.load(&test_user(43)).await.unwrap();
+    assert!(!app.polite());
+    assert!(app.spoilers());
+    assert_eq!(app.max_attempts(), 6);
+}
+
+/// Saves written as MessagePack load back the same, and a JSON save from before the
+/// switch is still read and then replaced.
+#[tokio::test]
+async fn msgpack_saves_round_trip() {
+    let save_dir = test_save_dir("msgpack");
+    std::fs::write(
+        format!("{}/42.json", save_dir),
+        r#"{"version": 3, "user_id": "42", "score": {"games": 3, "wins": 2}, "last_wordle": null, "settings": {"polite": true}}"#,
+    )
+    .unwrap();
+
+    let mut app = App::new("BadWordle".into(), vec!["hello".to_string()]);
+    app.set_save_dir(save_dir.clone());
+    app.set_save_format(SaveFormat::MessagePack);
+    app.load(&test_user(42)).await.unwrap();
+    assert!(app.polite());
+    app.start_game().await.unwrap();
+    app.save(&test_user(42)).await.unwrap();
+
+    assert!(!std::path::Path::new(&format!("{}/42.json", save_dir)).exists());
+    let contents = std::fs::read(format!("{}/42.msgpack", save_dir)).unwrap();
+    let save_data: serde_json::Value = SaveFormat::MessagePack.parse(&contents).unwrap();
+    assert_eq!(save_data["score"]["games"], 3);
+    assert_eq!(save_data["settings"]["polite"], true);
+
+    let mut app = App::new("BadWordle".into(), vec!["hello".to_string()]);
+    app.set_save_dir(save_dir.clone());
+    app.load(&test_user(42)).await.unwrap();
+    assert!(app.polite());
+    assert_eq!(app.score(&"42".to_string()).await.games, 3);
+    assert_eq!(app.wordle.as_ref().unwrap().target_word, "HELLO");
+}
+
+/// A save that can't be read is moved aside rather than overwritten, and the user
+/// starts over.
+#[tokio::test]
+async fn corrupt_save_moved_aside() {
+    let save_dir = test_save_dir("corrupt");
+    std::fs::write(format!("{}/42.json", save_dir), r#"{"version": 3, "user_"#).unwrap();
+
+    let mut app = App::new("BadWordle".into(), vec!["hello".to_string()]);
+    app.set_save_dir(save_dir.clone());
+
+    // Scans of every save skip it, but leave it in place.
+    app.end_season(0).await.unwrap();
+    assert!(std::path::Path::new(&format!("{}/42.json", save_dir)).exists());
+
+    assert!(app.load(&test_user(42)).await.is_err());
+    assert!(!std::path::Path::new(&format!("{}/42.json", save_dir)).exists());
+    assert_eq!(
+        std::fs::read_to_string(format!("{}/42.json.corrupt", save_dir)).unwrap(),
+        r#"{"version": 3, "user_"#
+    );
+
+    // The user plays on as new, and the corrupt save is kept.
+    app.start_game().await.unwrap();
+    app.save(&test_user(42)).await.unwrap();
+    app.load(&test_user(42)).await.unwrap();
+    assert!(app.is_playing());
+    assert!(std::path::Path::new(&format!("{}/42.json.corrupt", save_dir)).exists());
+}
+
+/// Past the game cap, the least recently active chat's game is saved and dropped.
+#[tokio::test]
+async fn max_games_evicts_oldest() {
+    let save_dir = test_save_dir("max-games");
+    let mut app = App::new("BadWordle".into(), vec!["hello".to_string()]);
+    app.set_save_dir(save_dir.clone());
+    app.set_save_delay(Duration::from_secs(3600));
+    app.set_max_games(Some(2));
+
+    for id in [1, 2, 3] {
+        app.start_game().await.unwrap();
+        app.save(&test_user(id)).await.unwrap();
+        app.track_game(id, &test_user(id)).await;
+        tokio::time::sleep(Duration::from_millis(2)).await;
+    }
+
+    assert!(app.tracked_game(1).await.is_none());
+    assert!(app.tracked_game(2).await.is_some());
+    assert!(app.tracked_game(3).await.is_some());
+    assert!(std::path::Path::new(&format!("{}/1.json", save_dir)).exists());
+    assert!(!std::path::Path::new(&format!("{}/2.json", save_dir)).exists());
+
+    // The evicted game is still there for its player.
+    let mut app = App::new("BadWordle".into(), vec!["hello".to_string()]);
+    app.set_save_dir(save_dir);
+    app.load(&test_user(1)).await.unwrap();
+    assert!(app.is_playing());
+
+    // A chat's game is reloaded from disk on its next message after it's evicted.
+    let save_dir = test_save_dir("max-games-bot");
+    let mut app = App::new("BadWordle".into(), vec!["hello".to_string()]);
+    app.set_save_dir(save_dir.clone());
+    app.set_save_delay(Duration::from_secs(3600));
+    app.set_max_games(Some(1));
+    let (fakeserver, shutdown_notifier, shutdown_tx) = start_bot(app);
+
+    let chat1 = fakeserver.create_chat("qubyte").await;
+    send_as(&chat1, 1, "/new").await;
+    chat1.recv_update().await.unwrap();
+    send_as(&chat1, 1, "world").await;
+    chat1.recv_update().await.unwrap();
+
+    let chat2 = fakeserver.create_chat("other").await;
+    send_as(&chat2, 2, "/new").await;
+    chat2.recv_update().await.unwrap();
+    assert!(std::path::Path::new(&format!("{}/1.json", save_dir)).exists());
+
+    send_as(&chat1, 1, "belly").await;
+    assert!(chat1
+        .recv_update()
+        .await
+        .unwrap()
+        .to_string()
+        .contains("Attempts: `W` `O` `R` `L` `D` `B` `E` `Y`"));
+
+    // The chat keeps nothing in memory once it's evicted again, so without the save
+    // it starts over.
+    send_as(&chat2, 2, "world").await;
+    chat2.recv_update().await.unwrap();
+    std::fs::remove_file(format!("{}/1.json", save_dir)).unwrap();
+    send_as(&chat1, 1, "belly").await;
+    let reply = chat1.recv_update().await.unwrap().to_string();
+    assert!(!reply.contains("`W` `O`"), "{}", reply);
+
+    stop_bot(shutdown_notifier, shutdown_tx).await;
+}
+
+/// A cap of 0 still keeps the game that was just played.
+#[tokio::test]
+async fn max_games_zero_keeps_current_game() {
+    let mut app = App::new("BadWordle".into(), vec!["hello".to_string()]);
+    app.set_save_dir(test_save_dir("max-games-zero"));
+    app.set_save_delay(Duration::from_secs(3600));
+    app.set_max_games(Some(0));
+
+    for id in [1, 2] {
+        app.start_game().await.unwrap();
+        app.save(&test_user(id)).await.unwrap();
+        app.track_game(id, &test_user(id)).await;
+    }
+
+    assert!(app.tracked_game(1).await.is_none());
+    assert!(app.tracked_game(2).await.is_some());
+}
+
+/// `/settings` shows the sender's preferences as they change.
+#[tokio::test]
+async fn settings_command() {
+    let mut app = App::new("BadWordle".into(), vec!["hello".to_string()]);
+    app.set_save_dir(test_save_dir("settings"));
+    let (fakeserver, shutdown_notifier, shutdown_tx) = start_bot(app);
+
+    let chat = fakeserver.create_chat("qubyte").await;
+    chat.send_text("/settings").await.unwrap();
+    assert_eq!(
+        chat.recv_update().await.unwrap().to_string(),
+        "Your settings:\nWord length: any (/length)\nGuesses: 6 (/tries)\nTone: rude (/tone)\nSpoilers: on (/spoilers)\nPlain letters: off (/plainletters)\nTerse: off (/terse)\nAssist: off (/assist)\nAnalytics: on (/analytics)"
+    );
+
+    chat.send_text("/spoilers off").await.unwrap();
+    chat.recv_update().await.unwrap();
+    chat.send_text("/settings").await.unwrap();
+    assert!(chat
+        .recv_update()
+        .await
+        .unwrap()
+        .to_string()
+        .contains("Spoilers: off (/spoilers)"));
+
+    stop_bot(shutdown_notifier, shutdown_tx).await;
+}
+
+/// Admins can `/peek` at the target of another chat's game.
+#[tokio::test]
+async fn peek_command() {
+    let mut app = App::new("BadWordle".into(), vec!["hello".to_string()]);
+    app.admin_user = Some("admin".into());
+    let (fakeserver, shutdown_notifier, shutdown_tx) = start_bot(app);
+
+    let chat = fakeserver.create_chat("qubyte").await;
+    chat.send_text("hi").await.unwrap();
+    chat.recv_update().await.unwrap();
+
+    let admin = fakeserver.create_chat("admin").await;
+    admin
+        .send_text(format!("/peek {}", chat.chat_id).as_str())
+        .await
+        .unwrap();
+    assert!(admin
+        .recv_update()
+        .await
+        .unwrap()
+        .to_string()
+        .contains("playing HELLO"));
+
+    admin.send_text("/peek 12345").await.unwrap();
+    assert_eq!(
+        admin.recv_update().await.unwrap().to_string(),
+        "No game in progress for chat 12345."
+    );
+
+    // Finished games aren't kept.
+    chat.send_text("hello").await.unwrap();
+    chat.recv_update().await.unwrap();
+    admin
+        .send_text(format!("/peek {}", chat.chat_id).as_str())
+        .await
+        .unwrap();
+    assert_eq!(
+        admin.recv_update().await.unwrap().to_string(),
+        format!("No game in progress for chat {}.", chat.chat_id)
+    );
+
+    // Bad chat IDs get a reply, not a handler error.
+    admin.send_text("/peek abc").await.unwrap();
+    assert_eq!(
+        admin.recv_update().await.unwrap().to_string(),
+        "Sorry, \"abc\" is not a chat ID, which is a number like 12345. Usage: /peek <chat_id>"
+    );
+
+    // Players can't peek.
+    chat.send_text(format!("/peek {}", chat.chat_id).as_str())
+        .await
+        .unwrap();
+    assert_eq!(
+        chat.recv_update().await.unwrap().to_string(),
+        "You are not an admin."
+    );
+
+    stop_bot(shutdown_notifier, shutdown_tx).await;
+}
+
+/// Ending a season archives season scores and resets them, leaving all-time scores
+/// alone.
+#[tokio::test]
+async fn end_season_archives_scores() {
+    let save_dir = test_save_dir("end-season");
+    let mut app = App::new("BadWordle".into(), vec!["hello".to_string()]);
+    app.set_save_dir(save_dir.clone());
+    app.set_season_length(Some(Duration::from_secs(7 * 24 * 60 * 60)));
+    app.load_season().await.unwrap();
+
+    let user = test_user(42);
+    app.start_game().await.unwrap();
+    app.inc_games(&user).await;
+    app.inc_wins(&user).await;
+
+    // The season isn't over yet.
+    let started_at = app.season().await.started_at;
+    assert!(app
+        .maybe_end_season(started_at + 60)
+        .await
+        .unwrap()
+        .is_none());
+
+    let archive = app
+        .maybe_end_season(started_at + 7 * 24 * 60 * 60)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(archive.number, 1);
+    assert_eq!(archive.scores["42"].wins, 1);
+    assert!(std::path::Path::new(&format!("{}/season-1.json", save_dir)).exists());
+    assert_eq!(app.archived_seasons().await.unwrap(), vec![1]);
+
+    assert_eq!(app.season().await.number, 2);
+    assert_eq!(app.season_score(&"42".to_string()).await.games, 0);
+    assert_eq!(app.score(&"42".to_string()).await.games, 1);
+
+    // Reloading the user's save doesn't bring back last season's score.
+    app.load(&user).await.unwrap();
+    assert_eq!(app.season_score(&"42".to_string()).await.games, 0);
+}
+
+/// With --season-announce, ending a season sends its top players to the admin chat.
+#[tokio::test]
+async fn season_announce() {
+    let mut app = App::new("BadWordle".into(), vec!["hello".to_string()]);
+    app.set_save_dir(test_save_dir("season-announce"));
+    app.set_season_length(Some(Duration::from_secs(7 * 24 * 60 * 60)));
+    app.set_season_announce(true);
+    app.admin_user = Some("admin".into());
+    app.load_season().await.unwrap();
+    let (fakeserver, shutdown_notifier, shutdown_tx) = start_bot(app.clone());
+
+    let admin = fakeserver.create_chat("admin").await;
+    admin.send_text("/admin").await.unwrap();
+    admin.recv_update().await.unwrap();
+
+    app.start_game().await.unwrap();
+    for (id, name, wins) in [(1, "ann", 1), (2, "bob", 3), (3, "cat", 0), (4, "dan", 2)] {
+        let user = api::User {
+            username: Some(name.into()),
+            ..test_user(id)
+        };
+        for _ in 0..3 {
+            app.inc_games(&user).await;
+        }
+        for _ in 0..wins {
+            app.inc_wins(&user).await;
+        }
+    }
+
+    let client = Client::new("token".to_string().into()).with_post_handler(fakeserver.clone());
+    let api = Router::<App>::new(client).api;
+    let started_at = app.season().await.started_at;
+    app.rotate_season(Arc::clone(&api), started_at + 60)
+        .await
+        .unwrap();
+    app.rotate_season(api, started_at + 7 * 24 * 60 * 60)
+        .await
+        .unwrap();
+
+    assert_eq!(
+        admin.recv_update().await.unwrap().to_string(),
+        "`Season 1 is over! Top players:\n1. bob 100% (3/3)\n2. dan 67% (2/3)\n3. ann 33% (1/3)`"
+    );
+
+    stop_bot(shutdown_notifier, shutdown_tx).await;
+}
+
+/// A game left alone past the reminder period gets exactly one reminder, if the
+/// player opted in.
+#[tokio::test]
+async fn stale_game_reminded_once() {
+    let mut app = App::new("BadWordle".into(), vec!["hello".to_string()]);
+    app.set_reminder_after(Some(Duration::from_secs(24 * 60 * 60)));
+    let (fakeserver, shutdown_notifier, shutdown_tx) = start_bot(app.clone());
+
+    let chat = fakeserver.create_chat("qubyte").await;
+    chat.send_text("/reminders on").await.unwrap();
+    assert!(chat
+        .recv_update()
+        .await
+        .unwrap()
+        .to_string()
+        .starts_with("Reminders are on."));
+    chat.send_text("hi").await.unwrap();
+    chat.recv_update().await.unwrap();
+    chat.send_text("bello").await.unwrap();
+    chat.recv_update().await.unwrap();
+
+    let client = Client::new("token".to_string().into()).with_post_handler(fakeserver.clone());
+    let api = Router::<App>::new(client).api;
+    let now = std::time::Instant::now();
+
+    // Not stale yet.
+    assert_eq!(
+        app.send_reminders(Arc::clone(&api), now + Duration::from_secs(60 * 60))
+            .await,
+        0
+    );
+
+    let later = now + Duration::from_secs(25 * 60 * 60);
+    assert_eq!(app.send_reminders(Arc::clone(&api), later).await, 1);
+    assert!(chat
+        .recv_update()
+        .await
+        .unwrap()
+        .to_string()
+        .starts_with("Your game is still waiting for you!"));
+    assert_eq!(app.send_reminders(api, later).await, 0);
+
+    stop_bot(shutdown_notifier, shutdown_tx).await;
+}
+
+/// A guess that was meant for a game replaced by a racing `/new` isn't played
+/// against the new game.
+#[tokio::test]
+async fn guess_racing_new_game() {
+    let mut app = App::new(
+        "BadWordle".into(),
+        vec!["hello".to_string(), "world".to_string()],
+    );
+    let user = test_user(42);
+    app.start_game().await.unwrap();
+
+    // A guess arrives and notes the game it's for, then /new takes the lock first.
+    let game_serial = app.game_serial();
+    app.start_game().await.unwrap();
+    assert_ne!(app.game_serial(), game_serial);
+
+    let turn = app
+        .play_turn_in(game_serial, &user, "bello".into())
+        .await
+        .unwrap();
+    assert!(matches!(turn.move_, Move::GameChanged));
+    assert!(turn.board.attempts.is_empty());
+    assert!(app.wordle.as_ref().unwrap().attempts.is_empty());
+
+    // A guess meant for the new game is played as usual.
+    let turn = app
+        .play_turn_in(app.game_serial(), &user, "bello".into())
+        .await
+        .unwrap();
+    assert!(matches!(turn.move_, Move::Valid));
+    assert_eq!(app.wordle.as_ref().unwrap().attempts.len(), 1);
+}
+
+/// `/verify` checks a grid pasted on the lines after the command.
+#[tokio::test]
+async fn verify_command() {
+    let app = App::new("BadWordle".into(), vec!["hello".to_string()]);
+    let (fakeserver, shutdown_notifier, shutdown_tx) = start_bot(app);
+
+    let chat = fakeserver.create_chat("qubyte").await;
+    chat.send_text("/verify\nWordle 1,234 2/6\n\u{2B1B}\u{1F7E8}\n\u{1F7E9}\u{1F7E9}")
+        .await
+        .unwrap();
+    assert_eq!(
+        chat.recv_update().await.unwrap().to_string(),
+        "That grid is consistent, with 2 rows."
+    );
+
+    chat.send_text("/verify \u{1F7E9}\u{1F7E9}\n\u{2B1B}\u{1F7E8}")
+        .await
+        .unwrap();
+    assert_eq!(
+        chat.recv_update().await.unwrap().to_string(),
+        "That grid isn't consistent: row 2 comes after the word was solved."
+    );
+
+    stop_bot(shutdown_notifier, shutdown_tx).await;
+}
+
+/// `/start <code>` deep links start the challenged word.
+#[tokio::test]
+async fn start_challenge() {
+    let app = App::new(
+        "BadWordle".into(),
+        vec!["world".to_string(), "hello".to_string()],
+    );
+    let (fakeserver, shutdown_notifier, shutdown_tx) = start_bot(app);
+
+    let chat = fakeserver.create_chat("qubyte").await;
+    chat.send_text("/start ABC12").await.unwrap();
+    assert_eq!(
+        chat.recv_update().await.unwrap().to_string(),
+        "Sorry, that challenge is not valid."
+    );
+
+    let code = challenge::encode("hello");
+    assert_eq!(challenge::decode(&code).unwrap(), "HELLO");
+
+    chat.send_text(format!("/start {}", code).as_str())
+        .await
+        .unwrap();
+    assert!(chat
+        .recv_update()
+        .await
+        .unwrap()
+        .to_string()
+        .starts_with("Hi qubyte, Welcome to BadWordle!"));
+
+    chat.send_text("hello").await.unwrap();
+    assert!(chat
+        .recv_update()
+        .await
+        .unwrap()
+        .to_string()
+        .contains("You won"));
+
+    // Challenges can't smuggle in words the bot doesn't know.
+    chat.send_text(format!("/start {}", challenge::encode("zzzzz")).as_str())
+        .await
+        .unwrap();
+    assert_eq!(
+        chat.recv_update().await.unwrap().to_string(),
+        "Sorry qubyte, that challenge isn't a word I know."
+    );
+
+    stop_bot(shutdown_notifier, shutdown_tx).await;
+}
+
+/// With `avoid_similar_targets`, a target one edit away from the previous one is
+/// skipped while other words are available.
+#[tokio::test]
+async fn avoid_similar_targets() {
+    let target_words = vec![
+        "hello".to_string(),
+        "hells".to_string(),
+        "world".to_string(),
+    ];
+
+    let mut app = App::new("BadWordle".into(), target_words.clone());
+    assert_eq!(app.start_game().await.unwrap(), "HELLO");
+    assert_eq!(app.start_game().await.unwrap(), "HELLS");
+
+    let mut app = App::new("BadWordle".into(), target_words);
+    app.set_avoid_similar_targets(true);
+    assert_eq!(app.start_game().await.unwrap(), "HELLO");
+    assert_eq!(app.start_game().await.unwrap(), "WORLD");
+    assert_eq!(app.start_game().await.unwrap(), "HELLS");
+}
+
+/// `/validinfo` reports the valid word count, or whether a given word is valid.
+#[tokio::test]
+async fn validinfo_command() {
+    let mut app = App::new("BadWordle".into(), vec!["hello".to_string()]);
+    app.set_valid_words(["hello", "crane"].iter().map(|w| w.to_string()).collect());
+    let (fakeserver, shutdown_notifier, shutdown_tx) = start_bot(app);
+
+    let chat = fakeserver.create_chat("qubyte").await;
+    chat.send_text("/validinfo").await.unwrap();
+    assert!(chat
+        .recv_update()
+        .await
+        .unwrap()
+        .to_string()
+        .starts_with("2 valid words loaded"));
+
+    chat.send_text("/validinfo crane").await.unwrap();
+    assert_eq!(
+        chat.recv_update().await.unwrap().to_string(),
+        "CRANE is valid."
+    );
+
+    chat.send_text("/validinfo xyzzy").await.unwrap();
+    assert_eq!(
+        chat.recv_update().await.unwrap().to_string(),
+        "XYZZY is not valid."
+    );
+
+    stop_bot(shutdown_notifier, shutdown_tx).await;
+}
+
+/// `/replay` re-renders the last finished game, even after the bot restarts.
+#[tokio::test]
+async fn replay_command() {
+    let save_dir = test_save_dir("replay");
+    let mut app = App::new("BadWordle".into(), vec!["hello".to_string()]);
+    app.set_save_dir(save_dir.clone());
+    let (fakeserver, shutdown_notifier, shutdown_tx) = start_bot(app);
+
+    let chat = fakeserver.create_chat("qubyte").await;
+    chat.send_text("/replay").await.unwrap();
+    assert_eq!(
+        chat.recv_update().await.unwrap().to_string(),
+        "You haven't finished any games yet."
+    );
+
+    chat.send_text("hi").await.unwrap();
+    chat.recv_update().await.unwrap();
+    chat.send_text("hello").await.unwrap();
+    chat.recv_update().await.unwrap();
+    stop_bot(shutdown_notifier, shutdown_tx).await;
+
+    // Restart the bot, and start a new game before replaying the old one.
+    let mut app = App::new("BadWordle".into(), vec!["hello".to_string()]);
+    app.set_save_dir(save_dir);
+    let (fakeserver, shutdown_notifier, shutdown_tx) = start_bot(app);
+
+    let chat = fakeserver.create_chat("qubyte").await;
+    chat.send_text("/new").await.unwrap();
+    chat.recv_update().await.unwrap();
+    chat.send_text("/replay").await.unwrap();
+    let reply = chat.recv_update().await.unwrap().to_string();
+    info!("{}", reply);
+    assert!(reply.contains("You guessed HELLO in 1/6\\."));
+    assert!(reply.ends_with("\u{1F7E9}\u{1F7E9}\u{1F7E9}\u{1F7E9}\u{1F7E9}"));
+
+    stop_bot(shutdown_notifier, shutdown_tx).await;
+}
+
+/// Players who turn `/analytics off` don't show up in the admin log.
+#[tokio::test]
+async fn analytics_off_suppresses_admin_log() {
+    let mut app = App::new("BadWordle".into(), vec!["hello".to_string()]);
+    app.set_save_dir(test_save_dir("analytics"));
+    app.admin_user = Some("admin".into());
+    let (fakeserver, shutdown_notifier, shutdown_tx) = start_bot(app);
+
+    let admin = fakeserver.create_chat("admin").await;
+    admin.send_text("/admin").await.unwrap();
+    admin.recv_update().await.unwrap();
+
+    let private = fakeserver.create_chat("private").await;
+    send_as(&private, 2, "/analytics off").await;
+    assert!(private
+        .recv_update()
+        .await
+        .unwrap()
+        .to_string()
+        .starts_with("Analytics are off."));
+    send_as(&private, 2, "hi").await;
+    private.recv_update().await.unwrap();
+    send_as(&private, 2, "hello").await;
+    private.recv_update().await.unwrap();
+
+    // The next admin log comes from a player who didn't opt out.
+    let public = fakeserver.create_chat("public").await;
+    send_as(&public, 3, "hi").await;
+    public.recv_update().await.unwrap();
+    let log = admin.recv_update().await.unwrap().to_string();
+    assert!(log.contains("New user: public"), "{}", log);
+
+    stop_bot(shutdown_notifier, shutdown_tx).await;
+}
+
+/// `/admin` says so when admin functions are disabled, rather than rejecting the
+/// sender.
+#[tokio::test]
+async fn admin_disabled() {
+    let app = App::new("BadWordle".into(), vec!["hello".to_string()]);
+    let (fakeserver, shutdown_notifier, shutdown_tx) = start_bot(app);
+    let chat = fakeserver.create_chat("admin").await;
+    chat.send_text("/admin").await.unwrap();
+    assert_eq!(
+        chat.recv_update().await.unwrap().to_string(),
+        "Admin functions are disabled."
+    );
+    stop_bot(shutdown_notifier, shutdown_tx).await;
+
+    let mut app = App::new("BadWordle".into(), vec!["hello".to_string()]);
+    app.set_save_dir(test_save_dir("admin-disabled"));
+    app.admin_user = Some("admin".into());
+    let (fakeserver, shutdown_notifier, shutdown_tx) = start_bot(app);
+    let chat = fakeserver.create_chat("qubyte").await;
+    chat.send_text("/admin").await.unwrap();
+    assert_eq!(
+        chat.recv_update().await.unwrap().to_string(),
+        "You are not an admin."
+    );
+    stop_bot(shutdown_notifier, shutdown_tx).await;
+}
+
+/// Guesses made of emoji are rejected, even if they're as long as the target.
+#[tokio::test]
+async fn emoji_guesses_rejected() {
+    let app = App::new("BadWordle".into(), vec!["hello".to_string()]);
+    let (fakeserver, shutdown_notifier, shutdown_tx) = start_bot(app);
+
+    let chat = fakeserver.create_chat("qubyte").await;
+    chat.send_text("/new").await.unwrap();
+    chat.recv_update().await.unwrap();
+
+    chat.send_text("\u{1F600}\u{1F600}\u{1F600}\u{1F600}\u{1F600}")
+        .await
+        .unwrap();
+    assert_eq!(
+        chat.recv_update().await.unwrap().to_string(),
+        "Sorry qubyte, letters only, please\\."
+    );
+
+    stop_bot(shutdown_notifier, shutdown_tx).await;
+}
+
+/// Percentages round halves up, and are only 0% or 100% when they really are.
+#[test]
+fn format_percentages() {
+    assert_eq!(format_percentage(2, 3, 0), "67%");
+    assert_eq!(format_percentage(1, 8, 0), "13%");
+    assert_eq!(format_percentage(2, 3, 1), "66.7%");
+    assert_eq!(format_percentage(1, 20, 2), "5.00%");
+    assert_eq!(format_percentage(999, 1000, 0), "99%");
+    assert_eq!(format_percentage(1, 1000, 0), "1%");
+    assert_eq!(format_percentage(3, 3, 0), "100%");
+    assert_eq!(format_percentage(0, 3, 0), "0%");
+    assert_eq!(format_percentage(0, 0, 0), "0%");
+}
+
+/// Win rates are only shown once a player has played enough games.
+#[test]
+fn format_score_min_games() {
+    let score = Score {
+        games: 1,
+        wins: 1,
+        ..Default::default()
+    };
+    assert_eq!(format_score(&score, 5), "1/1");
+    assert_eq!(format_score(&score, 1), "100% (1/1)");
+}
+
+/// `/feedback` reaches the admin chat, and is rate limited.
+#[tokio::test]
+async fn feedback_command() {
+    let mut app = App::new("BadWordle".into(), vec!["hello".to_string()]);
+    app.set_save_dir(test_save_dir("feedback"));
+    app.admin_user = Some("admin".into());
+    let (fakeserver, shutdown_notifier, shutdown_tx) = start_bot(app);
+
+    let chat = fakeserver.create_chat("qubyte").await;
+    chat.send_text("/feedback hi").await.unwrap();
+    assert_eq!(
+        chat.recv_update().await.unwrap().to_string(),
+        "Sorry, feedback isn't being collected right now."
+    );
+
+    let admin = fakeserver.create_chat("admin").await;
+    admin.send_text("/admin").await.unwrap();
+    admin.recv_update().await.unwrap();
+
+    chat.send_text("/feedback the words are too hard")
+        .await
+        .unwrap();
+    assert_eq!(
+        chat.recv_update().await.unwrap().to_string(),
+        "Thanks for the feedback!"
+    );
+    let log = admin.recv_update().await.unwrap().to_string();
+    assert!(
+        log.contains("Feedback from qubyte (qubyte, 1): the words are too hard"),
+        "{}",
+        log
+    );
+
+    chat.send_text("/feedback again").await.unwrap();
+    assert!(chat
+        .recv_update()
+        .await
+        .unwrap()
+        .to_string()
+        .starts_with("You just sent feedback."));
+
+    stop_bot(shutdown_notifier, shutdown_tx).await;
+}
+
+/// With quiet admin logs, intermediate turns aren't logged, but wins are.
+#[tokio::test]
+async fn quiet_admin() {
+    let mut app = App::new("BadWordle".into(), vec!["hello".to_string()]);
+    app.set_save_dir(test_save_dir("quiet-admin"));
+    app.admin_user = Some("admin".into());
+    app.set_quiet_admin(true);
+    let (fakeserver, shutdown_notifier, shutdown_tx) = start_bot(app);
+
+    let admin = fakeserver.create_chat("admin").await;
+    admin.send_text("/admin").await.unwrap();
+    admin.recv_update().await.unwrap();
+
+    let chat = fakeserver.create_chat("qubyte").await;
+    chat.send_text("/new").await.unwrap();
+    chat.recv_update().await.unwrap();
+    assert!(admin
+        .recv_update()
+        .await
+        .unwrap()
+        .to_string()
+        .contains("starting a new game"));
+
+    chat.send_text("world").await.unwrap();
+    chat.recv_update().await.unwrap();
+    chat.send_text("hello").await.unwrap();
+    chat.recv_update().await.unwrap();
+    let log = admin.recv_update().await.unwrap().to_string();
+    assert!(
+        log.contains("'HELLO'") && log.contains("and won"),
+        "{}",
+        log
+    );
+
+    stop_bot(shutdown_notifier, shutdown_tx).await;
+}
+
+/// Game modes draw targets from their own pools.
+#[tokio::test]
+async fn game_modes() {
+    let mut app = App::new("BadWordle".into(), vec!["hello".to_string()]);
+    app.add_mode("5".into(), vec!["crane".to_string()]);
+    app.add_mode("7".into(), vec!["welcome".to_string()]);
+    let (fakeserver, shutdown_notifier, shutdown_tx) = start_bot(app);
+
+    let chat = fakeserver.create_chat("qubyte").await;
+    chat.send_text("/new7").await.unwrap();
+    assert!(chat
+        .recv_update()
+        .await
+        .unwrap()
+        .to_string()
+        .ends_with("Guess the 7-letter word."));
+    chat.send_text("welcome").await.unwrap();
+    assert!(chat
+        .recv_update()
+        .await
+        .unwrap()
+        .to_string()
+        .contains("You won"));
+
+    chat.send_text("/new5").await.unwrap();
+    chat.recv_update().await.unwrap();
+    chat.send_text("crane").await.unwrap();
+    assert!(chat
+        .recv_update()
+        .await
+        .unwrap()
+        .to_string()
+        .contains("You won"));
+
+    chat.send_text("/new9").await.unwrap();
+    assert_eq!(
+        chat.recv_update().await.unwrap().to_string(),
+        "Sorry, there's no 9 game."
+    );
+
+    // Plain /new goes back to the default words.
+    chat.send_text("/new").await.unwrap();
+    assert!(chat
+        .recv_update()
+        .await
+        .unwrap()
+        .to_string()
+        .ends_with("Guess the 5-letter word."));
+    chat.send_text("hello").await.unwrap();
+    assert!(chat
+        .recv_update()
+        .await
+        .unwrap()
+        .to_string()
+        .contains("You won"));
+
+    stop_bot(shutdown_notifier, shutdown_tx).await;
+}
+
+/// Losing to a rare word doesn't lower the win rate, but losing to a common one
+/// does.
+#[tokio::test]
+async fn mercy_for_rare_words() {
+    let mut app = App::new(
+        "BadWordle".into(),
+        vec!["hello".to_string(), "xyzzy".to_string()],
+    );
+    app.set_mercy(
+        [("XYZZY".to_string(), 1), ("HELLO".to_string(), 1000)].into(),
+        Some(10),
+    );
+    let user = test_user(42);
+    let id = user.id.to_string();
+
+    app.start_game_with("hello".into()).await.unwrap();
+    app.inc_games(&user).await;
+    app.play_turn(&user, "hello".into()).await.unwrap();
+    assert_eq!(app.score(&id).await.win_rate(), 100.0);
+
+    // Losing to a rare word is forgiven.
+    app.start_game_with("xyzzy".into()).await.unwrap();
+    app.inc_games(&user).await;
+    for _ in 0..6 {
+        app.play_turn(&user, "hello".into()).await.unwrap();
+    }
+    let score = app.score(&id).await;
+    assert_eq!((score.games, score.forgiven), (2, 1));
+    assert_eq!(score.win_rate(), 100.0);
+
+    // Losing to a common word isn't.
+    app.start_game_with("hello".into()).await.unwrap();
+    app.inc_games(&user).await;
+    for _ in 0..6 {
+        app.play_turn(&user, "world".into()).await.unwrap();
+    }
+    assert_eq!(app.score(&id).await.win_rate(), 50.0);
+}
+
+/// Edited messages aren't played as guesses.
+#[tokio::test]
+async fn edited_messages_ignored() {
+    let app = App::new("BadWordle".into(), vec!["hello".to_string()]);
+    let (fakeserver, shutdown_notifier, shutdown_tx) = start_bot(app);
+
+    let chat = fakeserver.create_chat("qubyte").await;
+    chat.send_text("/new").await.unwrap();
+    chat.recv_update().await.unwrap();
+
+    chat.send_update(Update::EditedMessage(api::Message {
+        from: Some(test_user(chat.chat_id)),
+        chat: api::Chat {
+            id: chat.chat_id,
+            ..Default::default()
+        },
+        text: Some("hello".into()),
+        ..Default::default()
+    }))
+    .await
+    .unwrap();
+    assert_eq!(
+        chat.recv_update().await.unwrap().to_string(),
+        "Edits don't count. Send your guess as a new message."
+    );
+
+    // The game goes on.
+    chat.send_text("hello").await.unwrap();
+    assert!(chat
+        .recv_update()
+        .await
+        .unwrap()
+        .to_string()
+        .contains("You won"));
+
+    stop_bot(shutdown_notifier, shutdown_tx).await;
+}
+
+/// Admins can page through the target words with `/listwords`.
+#[tokio::test]
+async fn listwords_command() {
+    let words = (0..60).map(|i| format!("word{:02}", i)).collect();
+    let mut app = App::new("BadWordle".into(), words);
+    app.admin_user = Some("admin".into());
+    let (fakeserver, shutdown_notifier, shutdown_tx) = start_bot(app);
+
+    let admin = fakeserver.create_chat("admin").await;
+    admin.send_text("/listwords").await.unwrap();
+    let reply = admin.recv_update().await.unwrap().to_string();
+    assert!(reply.starts_with("Target words, page 1 of 2 (60 words):\n\nword00, word01"));
+    assert!(reply.contains("word49\n\nType /listwords 2 for the next page."));
+
+    admin.send_text("/listwords 2").await.unwrap();
+    let reply = admin.recv_update().await.unwrap().to_string();
+    assert!(reply.ends_with(
+        "word50, word51, word52, word53, word54, word55, word56, word57, word58, word59"
+    ));
+
+    admin.send_text("/listwords 3").await.unwrap();
+    assert_eq!(
+        admin.recv_update().await.unwrap().to_string(),
+        "Page 3 is empty. There are 2 pages."
+    );
+
+    let chat = fakeserver.create_chat("qubyte").await;
+    chat.send_text("/listwords").await.unwrap();
+    assert_eq!(
+        chat.recv_update().await.unwrap().to_string(),
+        "You are not an admin."
+    );
+
+    stop_bot(shutdown_notifier, shutdown_tx).await;
+}
+
+/// With the no-repeats rule, guesses that repeat a letter are rejected.
+#[tokio::test]
+async fn no_repeated_letters() {
+    let mut app = App::new(
+        "BadWordle".into(),
+        vec!["hello".to_string(), "stone".to_string()],
+    );
+    app.set_no_repeated_letters(true);
+    let user = test_user(42);
+
+    // Targets that repeat a letter are skipped.
+    assert_eq!(app.start_game().await.unwrap(), "STONE");
+    assert!(matches!(
+        app.play_turn(&user, "APPLE".into()).await.unwrap().move_,
+        Move::RepeatedLetters
+    ));
+    assert!(matches!(
+        app.play_turn(&user, "CRANE".into()).await.unwrap().move_,
+        Move::Valid
+    ));
+}
+
+/// Right after a win, plain messages nudge the player instead of starting a game.
+#[tokio::test]
+async fn post_win_cooldown() {
+    let mut app = App::new("BadWordle".into(), vec!["hello".to_string()]);
+    app.set_post_win_cooldown(Duration::from_secs(60));
+    let (fakeserver, shutdown_notifier, shutdown_tx) = start_bot(app);
+
+    let chat = fakeserver.create_chat("qubyte").await;
+    chat.send_text("/new").await.unwrap();
+    chat.recv_update().await.unwrap();
+    chat.send_text("hello").await.unwrap();
+    chat.recv_update().await.unwrap();
+
+    chat.send_text("yay").await.unwrap();
+    assert_eq!(
+        chat.recv_update().await.unwrap().to_string(),
+        "Nice game! Say /new to play again."
+    );
+
+    chat.send_text("/new").await.unwrap();
+    assert!(chat
+        .recv_update()
+        .await
+        .unwrap()
+        .to_string()
+        .starts_with("Hi qubyte"));
+
+    stop_bot(shutdown_notifier, shutdown_tx).await;
+}
+
+/// Admins can overwrite a user's score, and it's saved.
+#[tokio::test]
+async fn setscore_command() {
+    let save_dir = test_save_dir("setscore");
+    let mut app = App::new("BadWordle".into(), vec!["hello".to_string()]);
+    app.set_save_dir(save_dir.clone());
+    app.admin_user = Some("admin".into());
+    let (fakeserver, shutdown_notifier, shutdown_tx) = start_bot(app);
+
+    let chat = fakeserver.create_chat("qubyte").await;
+    send_as(&chat, 42, "/new").await;
+    chat.recv_update().await.unwrap();
+
+    send_as(&chat, 42, "/setscore 42 9 9").await;
+    assert_eq!(
+        chat.recv_update().await.unwrap().to_string(),
+        "You are not an admin."
+    );
+
+    let admin = fakeserver.create_chat("admin").await;
+    admin.send_text("/setscore 42 5 3").await.unwrap();
+    assert!(admin
+        .recv_update()
+        .await
+        .unwrap()
+        .to_string()
+        .contains("wins can't be more than games"));
+
+    admin.send_text("/setscore 42 3 5").await.unwrap();
+    assert_eq!(
+        admin.recv_update().await.unwrap().to_string(),
+        "Score of user 42 set to 60% (3/5)."
+    );
+
+    send_as(&chat, 42, "/score").await;
+    assert_eq!(
+        chat.recv_update().await.unwrap().to_string(),
+        "Your score: 60% (3/5)"
+    );
+
+    // Players who haven't played yet get a save.
+    admin.send_text("/setscore 77 2 4").await.unwrap();
+    assert_eq!(
+        admin.recv_update().await.unwrap().to_string(),
+        "Score of user 77 set to 50% (2/4)."
+    );
+    assert!(std::path::Path::new(&format!("{}/77.json", save_dir)).exists());
+    stop_bot(shutdown_notifier, shutdown_tx).await;
+
+    // The score survives a restart.
+    let mut app = App::new("BadWordle".into(), vec!["hello".to_string()]);
+    app.set_save_dir(save_dir);
+    let user = test_user(42);
+    app.load(&user).await.unwrap();
+    assert_eq!(app.score(&user.id.to_string()).await.wins, 3);
+}
+
+/// Played words are capped to the number of target words when saving.
+#[tokio::test]
+async fn played_words_pruned_on_save() {
+    let save_dir = test_save_dir("prune-played");
+    let filename = format!("{}/42.json", save_dir);
+    let played_words = |filename: &str| {
+        let save_data: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(filename).unwrap()).unwrap();
+        let mut words = save_data["played_words"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|w| w.as_str().unwrap().to_string())
+            .collect::<Vec<_>>();
+        words.sort();
+        words
+    };
+
+    let mut app = App::new(
+        "BadWordle".into(),
+        vec!["hello".to_string(), "crane".to_string()],
+    );
+    app.set_save_dir(save_dir);
+    let user = test_user(42);
+
+    // Words that are no longer targets are dropped.
+    std::fs::write(
+        &filename,
+        r#"{"version": 2, "user_id": "42", "played_words": ["ABOUT", "OTHER", "THING", "WORDS", "HELLO"], "score": {"games": 5, "wins": 0}, "last_wordle": null}"#,
+    )
+    .unwrap();
+    app.load(&user).await.unwrap();
+    app.save(&user).await.unwrap();
+    assert_eq!(played_words(&filename), vec!["HELLO"]);
+
+    // Once every target has been played, the cycle starts over.
+    std::fs::write(
+        &filename,
+        r#"{"version": 2, "user_id": "42", "played_words": ["ABOUT", "HELLO", "CRANE"], "score": {"games": 3, "wins": 0}, "last_wordle": {"target_word": "CRANE", "attempts": []}}"#,
+    )
+    .unwrap();
+    app.load(&user).await.unwrap();
+    app.save(&user).await.unwrap();
+    assert_eq!(played_words(&filename), vec!["CRANE"]);
+}
+
+/// Reloading the word lists doesn't disturb games whose target was dropped from them.
+#[tokio::test]
+async fn reload_keeps_games_in_progress() {
+    let dir = test_save_dir("reload");
+    let target_file = format!("{}/targets.txt", dir);
+    let valid_file = format!("{}/valid.txt", dir);
+    std::fs::write(&target_file, "hello\n").unwrap();
+    std::fs::write(&valid_file, "hello\ncrane\nstone\n").unwrap();
+
+    let mut app = App::new("BadWordle".into(), vec!["hello".to_string()]);
+    app.admin_user = Some("admin".into());
+    app.set_word_files(target_file.clone(), vec![valid_file.clone()]);
+    app.reload_words().await.unwrap();
+    let (fakeserver, shutdown_notifier, shutdown_tx) = start_bot(app);
+
+    let chat = fakeserver.create_chat("qubyte").await;
+    chat.send_text("/new").await.unwrap();
+    chat.recv_update().await.unwrap();
+    chat.send_text("crane").await.unwrap();
+    chat.recv_update().await.unwrap();
+
+    // Only admins can reload.
+    chat.send_text("/reload").await.unwrap();
+    assert_eq!(
+        chat.recv_update().await.unwrap().to_string(),
+        "You are not an admin."
+    );
+
+    // Swap in a shorter list without the chat's target.
+    std::fs::write(&target_file, "stone\n").unwrap();
+    std::fs::write(&valid_file, "crane\nstone\n").unwrap();
+    let admin = fakeserver.create_chat("admin").await;
+    admin.send_text("/reload").await.unwrap();
+    assert_eq!(
+        admin.recv_update().await.unwrap().to_string(),
+        "Reloaded 1 target words and 2 valid words."
+    );
+
+    // The game in progress still finishes on its target.
+    chat.send_text("hello").await.unwrap();
+    assert!(chat
+        .recv_update()
+        .await
+        .unwrap()
+        .to_string()
+        .contains("You won"));
+
+    // The next game comes from the new list.
+    chat.send_text("/new").await.unwrap();
+    chat.recv_update().await.unwrap();
+    chat.send_text("stone").await.unwrap();
+    assert!(chat
+        .recv_update()
+        .await
+        .unwrap()
+        .to_string()
+        .contains("You won"));
+
+    // An empty list is refused, and the current lists are kept.
+    std::fs::write(&target_file, "").unwrap();
+    admin.send_text("/reload").await.unwrap();
+    assert!(admin
+        .recv_update()
+        .await
+        .unwrap()
+        .to_string()
+        .starts_with("Could not reload word lists: no target words found"));
+
+    stop_bot(shutdown_notifier, shutdown_tx).await;
+}
+
+/// Admins can check the bot's version and load with `/status`.
+#[tokio::test]
+async fn status_command() {
+    let mut app = App::new("BadWordle".into(), vec!["hello".to_string()]);
+    app.admin_user = Some("admin".into());
+    let (fakeserver, shutdown_notifier, shutdown_tx) = start_bot(app);
+
+    let chat = fakeserver.create_chat("qubyte").await;
+    chat.send_text("/new").await.unwrap();
+    chat.recv_update().await.unwrap();
+    chat.send_text("/status").await.unwrap();
+    assert_eq!(
+        chat.recv_update().await.unwrap().to_string(),
+        "You are not an admin."
+    );
+
+    let admin = fakeserver.create_chat("admin").await;
+    admin.send_text("/status").await.unwrap();
+    let reply = admin.recv_update().await.unwrap().to_string();
+    assert!(reply.starts_with(&format!("BadWordle v{}", env!("CARGO_PKG_VERSION"))));
+    assert!(reply.contains("Target words: 1"));
+    assert!(reply.ends_with("Active games: 1"));
+
+    stop_bot(shutdown_notifier, shutdown_tx).await;
+}
+
+/// The leaderboard ranks saved players by wins, with fresh scores from memory.
+#[tokio::test]
+async fn leaderboard_command() {
+    let save_dir = test_save_dir("leaderboard");
+    std::fs::write(
+        format!("{}/7.json", save_dir),
+        r#"{"user_id": "7", "user_handle": "ace", "score": {"games": 4, "wins": 3}}"#,
+    )
+    .unwrap();
+
+    let mut app = App::new("BadWordle".into(), vec!["hello".to_string()]);
+    app.set_save_dir(save_dir);
+    let (fakeserver, shutdown_notifier, shutdown_tx) = start_bot(app);
+
+    let chat = fakeserver.create_chat("qubyte").await;
+    chat.send_text("/leaderboard").await.unwrap();
+    assert_eq!(
+        chat.recv_update().await.unwrap().to_string(),
+        "Leaderboard:\n1. ace 75% (3/4)"
+    );
+
+    // A win shows up right away, even though the scan is cached.
+    // New players are named by their handle.
+    send_as(&chat, 42, "/new").await;
+    chat.recv_update().await.unwrap();
+    send_as(&chat, 42, "hello").await;
+    chat.recv_update().await.unwrap();
+    send_as(&chat, 42, "/leaderboard").await;
+    assert_eq!(
+        chat.recv_update().await.unwrap().to_string(),
+        "Leaderboard:\n1. ace 75% (3/4)\n2. qubyte 100% (1/1)"
+    );
+
+    stop_bot(shutdown_notifier, shutdown_tx).await;
+}
+
+/// `/rank` places players by win rate, once they've played enough games.
+#[tokio::test]
+async fn rank_command() {
+    let save_dir = test_save_dir("rank");
+    for (id, games, wins) in [(7, 4, 3), (8, 10, 4)] {
+        std::fs::write(
+            format!("{}/{}.json", save_dir, id),
+            format!(
+                r#"{{"user_id": "{}", "score": {{"games": {}, "wins": {}}}, "last_wordle": null}}"#,
+                id, games, wins
+            ),
+        )
+        .unwrap();
+    }
+
+    let mut app = App::new("BadWordle".into(), vec!["hello".to_string()]);
+    app.set_save_dir(save_dir.clone());
+    app.set_min_games_for_rate(3);
+    let (fakeserver, shutdown_notifier, shutdown_tx) = start_bot(app);
+
+    let top = fakeserver.create_chat("qubyte").await;
+    std::fs::write(
+        format!("{}/42.json", save_dir),
+        r#"{"user_id": "42", "score": {"games": 10, "wins": 9}, "last_wordle": null}"#,
+    )
+    .unwrap();
+    send_as(&top, 42, "/rank").await;
+    assert_eq!(
+        top.recv_update().await.unwrap().to_string(),
+        "You're #1 of 3 by win rate."
+    );
+
+    let newbie = fakeserver.create_chat("newbie").await;
+    send_as(&newbie, 43, "/rank").await;
+    assert_eq!(
+        newbie.recv_update().await.unwrap().to_string(),
+        "You need 3 more games to be ranked."
+    );
+
+    stop_bot(shutdown_notifier, shutdown_tx).await;
+}
+
+/// `/exportcsv` sends admins a CSV with a header and a row per saved player.
+#[tokio::test]
+async fn exportcsv_command() {
+    let save_dir = test_save_dir("exportcsv");
+    std::fs::write(
+        format!("{}/7.json", save_dir),
+        r#"{"user_id": "7", "user_handle": "ace", "score": {"games": 4, "wins": 3}}"#,
+    )
+    .unwrap();
+    std::fs::write(
+        format!("{}/8.json", save_dir),
+        r#"{"user_id": "8", "user_first_name": "Bo", "score": {"games": 3, "wins": 2}}"#,
+    )
+    .unwrap();
+
+    let mut app = App::new("BadWordle".into(), vec!["hello".to_string()]);
+    app.set_save_dir(save_dir);
+    app.admin_user = Some("admin".into());
+    let (fakeserver, shutdown_notifier, shutdown_tx) = start_bot(app);
+
+    let chat = fakeserver.create_chat("qubyte").await;
+    chat.send_text("/exportcsv").await.unwrap();
+    assert_eq!(
+        chat.recv_update().await.unwrap().to_string(),
+        "You are not an admin."
+    );
+
+    let admin = fakeserver.create_chat("admin").await;
+    admin.send_text("/admin").await.unwrap();
+    admin.recv_update().await.unwrap();
+    admin.send_text("/exportcsv").await.unwrap();
+    assert_eq!(
+        admin.recv_update().await.unwrap().to_string(),
+        "```\nid,handle,games,wins,win%\n7,ace,4,3,75.0\n8,,3,2,66.7\n```"
+    );
+
+    stop_bot(shutdown_notifier, shutdown_tx).await;
+}
+
+/// `/wordstats` reports how a word has fared, averaging the guesses of winners.
+#[tokio::test]
+async fn wordstats_command() {
+    let mut app = App::new("BadWordle".into(), vec!["hello".to_string()]);
+    app.set_save_dir(test_save_dir("wordstats"));
+    app.admin_user = Some("admin".into());
+    let (fakeserver, shutdown_notifier, shutdown_tx) = start_bot(app);
+
+    let chat = fakeserver.create_chat("qubyte").await;
+    for misses in [2, 4] {
+        chat.send_text("/new").await.unwrap();
+        chat.recv_update().await.unwrap();
+        for _ in 0..misses {
+            chat.send_text("bello").await.unwrap();
+            chat.recv_update().await.unwrap();
+        }
+        chat.send_text("hello").await.unwrap();
+        assert!(chat
+            .recv_update()
+            .await
+            .unwrap()
+            .to_string()
+            .contains("You won"));
+    }
+
+    let admin = fakeserver.create_chat("admin").await;
+    admin.send_text("/admin").await.unwrap();
+    admin.recv_update().await.unwrap();
+    admin.send_text("/wordstats hello").await.unwrap();
+    assert_eq!(
+        admin.recv_update().await.unwrap().to_string(),
+        "HELLO: served 2, won 2, lost 0. Average winning guesses: 4.0."
+    );
+    admin.send_text("/wordstats crane").await.unwrap();
+    assert_eq!(
+        admin.recv_update().await.unwrap().to_string(),
+        "CRANE hasn't been served yet."
+    );
+
+    stop_bot(shutdown_notifier, shutdown_tx).await;
+}
+
+/// With --hide-word-on-loss, losing or giving up doesn't show the target until an
+/// admin reveals it.
+#[tokio::test]
+async fn hide_word_on_loss() {
+    let mut app = App::new("BadWordle".into(), vec!["hello".to_string()]);
+    app.set_hide_word_on_loss(true);
+    app.admin_user = Some("admin".into());
+    let (fakeserver, shutdown_notifier, shutdown_tx) = start_bot(app);
+
+    let chat = fakeserver.create_chat("qubyte").await;
+    chat.send_text("/new").await.unwrap();
+    chat.recv_update().await.unwrap();
+    for _ in 0..5 {
+        chat.send_text("bello").await.unwrap();
+        chat.recv_update().await.unwrap();
+    }
+    chat.send_text("bello").await.unwrap();
+    let reply = chat.recv_update().await.unwrap().to_string();
+    assert!(reply.contains("You lost"));
+    assert!(reply.contains("revealed when the puzzle closes"));
+    assert!(!reply.contains("HELLO"));
+
+    chat.send_text("/replay").await.unwrap();
+    assert!(!chat
+        .recv_update()
+        .await
+        .unwrap()
+        .to_string()
+        .contains("HELLO"));
+
+    // Giving up follows the same policy.
+    chat.send_text("/new").await.unwrap();
+    chat.recv_update().await.unwrap();
+    chat.send_text("/giveup").await.unwrap();
+    let reply = chat.recv_update().await.unwrap().to_string();
+    assert!(reply.contains("You gave up"));
+    assert!(!reply.contains("HELLO"));
+
+    chat.send_text("/giveup").await.unwrap();
+    assert_eq!(
+        chat.recv_update().await.unwrap().to_string(),
+        "You don't have a game in progress. Type /new to start one."
+    );
+
+    let admin = fakeserver.create_chat("admin").await;
+    admin.send_text("/admin").await.unwrap();
+    admin.recv_update().await.unwrap();
+    admin.send_text("/reveal").await.unwrap();
+    assert_eq!(
+        chat.recv_update().await.unwrap().to_string(),
+        "The puzzle is closed! The word was HELLO."
+    );
+    assert_eq!(
+        admin.recv_update().await.unwrap().to_string(),
+        "Revealed the held back words to 1 chats."
+    );
+
+    chat.send_text("/replay").await.unwrap();
+    assert!(chat
+        .recv_update()
+        .await
+        .unwrap()
+        .to_string()
+        .contains("The word was HELLO"));
+
+    stop_bot(shutdown_notifier, shutdown_tx).await;
+}
+
+/// Held back words are saved, so a restart doesn't reveal or lose them.
+#[tokio::test]
+async fn held_back_words_survive_restart() {
+    let save_dir = test_save_dir("held-back");
+    let mut app = App::new("BadWordle".into(), vec!["hello".to_string()]);
+    app.set_save_dir(save_dir.clone());
+    app.set_hide_word_on_loss(true);
+    app.hold_back_word(42, "HELLO").await;
+
+    let mut app = App::new("BadWordle".into(), vec!["hello".to_string()]);
+    app.set_save_dir(save_dir);
+    app.set_hide_word_on_loss(true);
+    assert!(!app.is_held_back(42, "HELLO").await);
+    app.load_held_back_words().await.unwrap();
+    assert!(app.is_held_back(42, "HELLO").await);
+}
+
+/// Words whose chat can't be messaged stay held back for the next reveal.
+#[tokio::test]
+async fn held_back_words_kept_when_reveal_fails() {
+    let save_dir = test_save_dir("held-back-reveal");
+    let mut app = App::new("BadWordle".into(), vec!["hello".to_string()]);
+    app.set_save_dir(save_dir.clone());
+    app.set_hide_word_on_loss(true);
+    app.hold_back_word(42, "HELLO").await;
+
+    let client = Client::new("token".to_string().into()).with_post_handler_fn(|_, _| {
+        Ok(r#"{"ok": false, "description": "Bad Request: chat not found"}"#.into())
+    });
+    assert_eq!(app.reveal_words(Router::<App>::new(client).api).await, 0);
+    assert!(app.is_held_back(42, "HELLO").await);
+
+    let mut app = App::new("BadWordle".into(), vec!["hello".to_string()]);
+    app.set_save_dir(save_dir);
+    app.load_held_back_words().await.unwrap();
+    assert!(app.is_held_back(42, "HELLO").await);
+}
+
+/// Admins can have the bot solve a word with its solver.
+#[tokio::test]
+async fn solve_command() {
+    let mut app = App::new("BadWordle".into(), vec!["hello".to_string()]);
+    app.set_valid_words(
+        ["hello", "belly", "jelly", "crane", "shell", "world"]
+            .map(String::from)
+            .into(),
+    );
+    app.admin_user = Some("admin".into());
+    let (fakeserver, shutdown_notifier, shutdown_tx) = start_bot(app);
+
+    let admin = fakeserver.create_chat("admin").await;
+    admin.send_text("/solve hello").await.unwrap();
+    let reply = admin.recv_update().await.unwrap().to_string();
+    assert!(reply.starts_with("Solved HELLO in "), "{}", reply);
+    assert!(reply.ends_with("HELLO"));
+
+    admin.send_text("/solve xylyl").await.unwrap();
+    assert_eq!(
+        admin.recv_update().await.unwrap().to_string(),
+        "XYLYL isn't a valid word."
+    );
+
+    stop_bot(shutdown_notifier, shutdown_tx).await;
+}
+
+/// Admins can try out a word in a practice game, which doesn't count toward their
+/// score.
+#[tokio::test]
+async fn testword_command() {
+    let mut app = App::new("BadWordle".into(), vec!["hello".to_string()]);
+    app.admin_user = Some("admin".into());
+    app.set_save_dir(test_save_dir("testword"));
+    let (fakeserver, shutdown_notifier, shutdown_tx) = start_bot(app);
+
+    let chat = fakeserver.create_chat("qubyte").await;
+    chat.send_text("/testword apple").await.unwrap();
+    assert_eq!(
+        chat.recv_update().await.unwrap().to_string(),
+        "You are not an admin."
+    );
+
+    // The practice game replaces the admin's saved game, which is loaded before
+    // every guess.
+    let admin = fakeserver.create_chat("admin").await;
+    admin.send_text("/new").await.unwrap();
+    admin.recv_update().await.unwrap();
+    admin.send_text("/testword apple").await.unwrap();
+    assert_eq!(
+        admin.recv_update().await.unwrap().to_string(),
+        "Practice game with APPLE started. It won't count toward your score. Send your guess."
+    );
+    admin.send_text("apple").await.unwrap();
+    let reply = admin.recv_update().await.unwrap().to_string();
+    assert!(reply.contains("You won"), "{}", reply);
+    assert!(reply.contains("0/1"), "{}", reply);
+
+    stop_bot(shutdown_notifier, shutdown_tx).await;
+}
+
+/// Operators can pick the emoji shown on wins and losses.
+#[tokio::test]
+async fn celebration_emoji() {
+    let mut app = App::new("BadWordle".into(), vec!["hello".to_string()]);
+    assert!(app
+        .set_celebration_emoji(Some("\u{1F389}\u{1F389}".into()), None)
+        .is_err());
+    app.set_celebration_emoji(Some("\u{1F389}".into()), Some("\u{1F44D}\u{1F3FD}".into()))
+        .unwrap();
+    let (fakeserver, shutdown_notifier, shutdown_tx) = start_bot(app);
+
+    let chat = fakeserver.create_chat("qubyte").await;
+    chat.send_text("/new").await.unwrap();
+    chat.recv_update().await.unwrap();
+    chat.send_text("hello").await.unwrap();
+    assert!(chat
+        .recv_update()
+        .await
+        .unwrap()
+        .to_string()
+        .contains("You won\\! \u{1F389}"));
+
+    chat.send_text("/new").await.unwrap();
+    chat.recv_update().await.unwrap();
+    for _ in 0..5 {
+        chat.send_text("world").await.unwrap();
+        chat.recv_update().await.unwrap();
+    }
+    chat.send_text("world").await.unwrap();
+    assert!(chat
+        .recv_update()
+        .await
+        .unwrap()
+        .to_string()
+        .contains("Target word: HELLO \u{1F44D}\u{1F3FD}"));
+
+    stop_bot(shutdown_notifier, shutdown_tx).await;
+}
+
+/// With --no-emoji, boards and win and loss messages are plain ASCII, even for
+/// players who didn't pick plain letters.
+#[tokio::test]
+async fn no_emoji() {
+    let mut app = App::new("BadWordle".into(), vec!["hello".to_string()]);
+    app.set_no_emoji(true);
+    let (fakeserver, shutdown_notifier, shutdown_tx) = start_bot(app);
+
+    let chat = fakeserver.create_chat("qubyte").await;
+    chat.send_text("/new").await.unwrap();
+    chat.recv_update().await.unwrap();
+    for _ in 0..6 {
+        chat.send_text("bello").await.unwrap();
+        let reply = chat.recv_update().await.unwrap().to_string();
+        assert!(reply.is_ascii(), "{}", reply);
+    }
+
+    chat.send_text("/new").await.unwrap();
+    chat.recv_update().await.unwrap();
+    chat.send_text("hello").await.unwrap();
+    let reply = chat.recv_update().await.unwrap().to_string();
+    assert!(reply.contains("You won"));
+    assert!(reply.is_ascii(), "{}", reply);
+
+    stop_bot(shutdown_notifier, shutdown_tx).await;
+}
+
+/// With `/terse on`, valid guesses get the board and attempted letters, without a
+/// comment or legend.
+#[tokio::test]
+async fn terse_replies() {
+    let mut app = App::new("BadWordle".into(), vec!["hello".to_string()]);
+    app.set_save_dir(test_save_dir("terse"));
+    let (fakeserver, shutdown_notifier, shutdown_tx) = start_bot(app);
+
+    let chat = fakeserver.create_chat("qubyte").await;
+    chat.send_text("/terse on").await.unwrap();
+    chat.recv_update().await.unwrap();
+    chat.send_text("/new").await.unwrap();
+    chat.recv_update().await.unwrap();
+    chat.send_text("world").await.unwrap();
+    let reply = chat.recv_update().await.unwrap().to_string();
+    assert!(reply.contains("Attempts: "));
+    assert!(!reply.contains("Guess another word?"));
+    assert!(!reply.contains("right spot"));
+
+    chat.send_text("/terse off").await.unwrap();
+    chat.recv_update().await.unwrap();
+    chat.send_text("bello").await.unwrap();
+    assert!(chat
+        .recv_update()
+        .await
+        .unwrap()
+        .to_string()
+        .contains("Guess another word?"));
+
+    stop_bot(shutdown_notifier, shutdown_tx).await;
+}
+
+/// With `/assist on`, the guess that uses up half the attempts reveals a letter the
+/// player hasn't found yet, and earlier guesses don't.
+#[tokio::test]
+async fn assist_reveals_letter_halfway() {
+    let mut app = App::new("BadWordle".into(), vec!["hello".to_string()]);
+    app.set_save_dir(test_save_dir("assist"));
+    let (fakeserver, shutdown_notifier, shutdown_tx) = start_bot(app);
+
+    let chat = fakeserver.create_chat("qubyte").await;
+    chat.send_text("/assist on").await.unwrap();
+    chat.recv_update().await.unwrap();
+    chat.send_text("/new").await.unwrap();
+    chat.recv_update().await.unwrap();
+    for guess in ["bello", "world"] {
+        chat.send_text(guess).await.unwrap();
+        assert!(!chat
+            .recv_update()
+            .await
+            .unwrap()
+            .to_string()
+            .contains("halfway"));
+    }
+
+    // H is the only letter not yet in the right spot.
+    chat.send_text("cello").await.unwrap();
+    assert!(chat
+        .recv_update()
+        .await
+        .unwrap()
+        .to_string()
+        .contains("here's a letter: number 1 is H"));
+
+    chat.send_text("jello").await.unwrap();
+    assert!(!chat
+        .recv_update()
+        .await
+        .unwrap()
+        .to_string()
+        .contains("halfway"));
+
+    stop_bot(shutdown_notifier, shutdown_tx).await;
+}
+
+/// `/grid` shows the share grid of a game in progress, one row per attempt.
+#[tokio::test]
+async fn grid_command() {
+    let app = App::new("BadWordle".into(), vec!["hello".to_string()]);
+    let (fakeserver, shutdown_notifier, shutdown_tx) = start_bot(app);
+
+    let chat = fakeserver.create_chat("qubyte").await;
+    chat.send_text("/grid").await.unwrap();
+    assert_eq!(
+        chat.recv_update().await.unwrap().to_string(),
+        "You don't have a game in progress. Type /new to start one."
+    );
+
+    chat.send_text("/new").await.unwrap();
+    chat.recv_update().await.unwrap();
+    for guess in ["bello", "world"] {
+        chat.send_text(guess).await.unwrap();
+        chat.recv_update().await.unwrap();
+    }
+    chat.send_text("/grid").await.unwrap();
+    assert_eq!(
+        chat.recv_update().await.unwrap().to_string(),
+        "\u{2B1B}\u{1F7E9}\u{1F7E9}\u{1F7E9}\u{1F7E9}\n\u{2B1B}\u{1F7E8}\u{2B1B}\u{1F7E9}\u{2B1B}"
+    );
+
+    stop_bot(shutdown_notifier, shutdown_tx).await;
+}
+
+/// A guess sent twice in quick succession, like some clients do, is only played once.
+#[tokio::test]
+async fn duplicate_guess_ignored() {
+    let mut app = App::new("BadWordle".into(), vec!["hello".to_string()]);
+    app.set_duplicate_window(Duration::from_secs(60));
+    let (fakeserver, shutdown_notifier, shutdown_tx) = start_bot(app);
+
+    let chat = fakeserver.create_chat("qubyte").await;
+    chat.send_text("/new").await.unwrap();
+    chat.recv_update().await.unwrap();
+    chat.send_text("bello").await.unwrap();
+    chat.recv_update().await.unwrap();
+    chat.send_text("bello").await.unwrap();
+    chat.send_text("hello").await.unwrap();
+    let reply = chat.recv_update().await.unwrap().to_string();
+    assert!(reply.contains("You won"));
+
+    chat.send_text("/replay").await.unwrap();
+    assert!(chat
+        .recv_update()
+        .await
+        .unwrap()
+        .to_string()
+        .contains("You guessed HELLO in 2/6"));
+
+    stop_bot(shutdown_notifier, shutdown_tx).await;
+}
+
+/// A guess wrapped in quotes, like copy-paste sometimes sends it, is still played.
+#[tokio::test]
+async fn quoted_guess() {
+    let app = App::new("BadWordle".into(), vec!["hello".to_string()]);
+    let (fakeserver, shutdown_notifier, shutdown_tx) = start_bot(app);
+
+    let chat = fakeserver.create_chat("qubyte").await;
+    chat.send_text("/new").await.unwrap();
+    chat.recv_update().await.unwrap();
+    chat.send_text("\u{201C}hello\u{201D}").await.unwrap();
+    assert!(chat
+        .recv_update()
+        .await
+        .unwrap()
+        .to_string()
+        .contains("You won"));
+
+    stop_bot(shutdown_notifier, shutdown_tx).await;
+}
+
+/// With accent-insensitive matching, unaccented guesses win against accented targets,
+/// and the target keeps its accents.
+#[tokio::test]
+async fn accent_insensitive() {
+    let mut app = App::new("BadWordle".into(), vec!["café".to_string()]);
+    app.set_valid_words(["café".to_string(), "bleu".to_string()].into());
+    app.set_accent_insensitive(true);
+    let user = test_user(42);
+
+    assert_eq!(app.start_game().await.unwrap(), "CAFÉ");
+    assert!(matches!(
+        app.play_turn(&user, "CAFE".into()).await.unwrap().move_,
+        Move::Won
+    ));
+
+    app.start_game().await.unwrap();
+    for _ in 0..6 {
+        app.play_turn(&user, "BLEU".into()).await.unwrap();
+    }
+    assert_eq!(app.wordle.as_ref().unwrap().target_word, "CAFÉ");
+
+    // Without the option, the accent has to match.
+    app.set_accent_insensitive(false);
+    app.start_game().await.unwrap();
+    assert!(matches!(
+        app.play_turn(&user, "CAFE".into()).await.unwrap().move_,
+        Move::InvalidWord
+    ));
+}
+
+/// Targets longer than the maximum word length are skipped.
+#[tokio::test]
+async fn long_targets_skipped() {
+    let mut app = App::new(
+        "BadWordle".into(),
+        vec!["incomprehensibilities".to_string(), "crane".to_string()],
+    );
+    assert_eq!(app.start_game().await.unwrap(), "CRANE");
+
+    assert!(app.set_max_word_length(4).is_ok());
+    assert!(app.start_game().await.is_err());
+    assert!(app.start_game_with("crane".into()).await.is_err());
+    assert!(app
+        .set_max_word_length(crate::wordle::MAX_WORD_LENGTH + 1)
+        .is_err());
+}
+
+/// Under --strict-targets, targets missing from the valid-word files are skipped.
+#[tokio::test]
+async fn strict_targets_skip_unlisted() {
+    let mut app = App::new(
+        "BadWordle".into(),
+        vec!["zzzzz".to_string(), "crane".to_string()],
+    );
+    app.set_valid_words(["crane", "apple"].iter().map(|w| w.to_string()).collect());
+    assert_eq!(app.start_game().await.unwrap(), "ZZZZZ");
+
+    app.set_strict_targets(true);
+    for _ in 0..3 {
+        assert_eq!(app.start_game().await.unwrap(), "CRANE");
+    }
+}
+
+/// Giving up counts as a loss unless --giveup-counts is false, in which case the
+/// game doesn't count at all.
+#[tokio::test]
+async fn giveup_counts() {
+    for (giveup_counts, games) in [(true, 1), (false, 0)] {
+        let mut app = App::new("BadWordle".into(), vec!["hello".to_string()]);
+        app.set_giveup_counts(giveup_counts);
+        let user = test_user(42);
+
+        app.start_game().await.unwrap();
+        app.inc_games(&user).await;
+        let turn = app.give_up(&user).await.unwrap();
+        assert!(matches!(turn.move_, Move::Lost));
+        assert_eq!(
+            turn.score,
+            Score {
+                games,
+                wins: 0,
+                forgiven: 0
+            }
+        );
+        assert_eq!(app.score(&"42".to_string()).await.games, games);
+        assert_eq!(app.recent_games().len(), games as usize);
+        assert_eq!(app.word_stats("hello").await.unwrap().losses, games);
+        assert!(app.give_up(&user).await.is_err());
+    }
+}
+
+/// A turn returns the board, target and score as they stand after the move.
+#[tokio::test]
+async fn play_turn_result() {
+    let mut app = App::new("BadWordle".into(), vec!["hello".to_string()]);
+    let user = test_user(42);
+    app.start_game().await.unwrap();
+    app.inc_games(&user).await;
+
+    let turn = app.play_turn(&user, "world".into()).await.unwrap();
+    assert!(matches!(turn.move_, Move::Valid));
+    assert_eq!(turn.target, "HELLO");
+    assert_eq!(
+        render_game(&turn.board, true, false),
+        render_game(&app.wordle.as_ref().unwrap().game().unwrap(), true, false)
+    );
+
+    // Rejected guesses still come with the current board.
+    let turn = app.play_turn(&user, "hi".into()).await.unwrap();
+    assert!(matches!(turn.move_, Move::InvalidLength));
+    assert_eq!(turn.board.attempts.len(), 1);
+
+    let turn = app.play_turn(&user, "hello".into()).await.unwrap();
+    assert!(matches!(turn.move_, Move::Won));
+    assert_eq!((turn.score.wins, turn.score.games), (1, 1));
+}
+
+/// Admins can count the games in progress with `/active`.
+#[tokio::test]
+async fn active_command() {
+    let mut app = App::new("BadWordle".into(), vec!["hello".to_string()]);
+    app.admin_user = Some("admin".into());
+    let (fakeserver, shutdown_notifier, shutdown_tx) = start_bot(app);
+
+    let chat1 = fakeserver.create_chat("qubyte").await;
+    let chat2 = fakeserver.create_chat("mo").await;
+    for chat in [&chat1, &chat2] {
+        chat.send_text("/new").await.unwrap();
+        chat.recv_update().await.unwrap();
+    }
+    chat1.send_text("hello").await.unwrap();
+    chat1.recv_update().await.unwrap();
+
+    let admin = fakeserver.create_chat("admin").await;
+    admin.send_text("/active").await.unwrap();
+    assert_eq!(
+        admin.recv_update().await.unwrap().to_string(),
+        "Games in progress: 1"
+    );
+
+    chat1.send_text("/active").await.unwrap();
+    assert_eq!(
+        chat1.recv_update().await.unwrap().to_string(),
+        "You are not an admin."
+    );
+
+    stop_bot(shutdown_notifier, shutdown_tx).await;
+}
+
+/// While paused, new games are refused but games in progress go on.
+#[tokio::test]
+async fn pause_and_resume() {
+    let mut app = App::new("BadWordle".into(), vec!["hello".to_string()]);
+    app.admin_user = Some("admin".into());
+    let (fakeserver, shutdown_notifier, shutdown_tx) = start_bot(app);
+
+    let chat = fakeserver.create_chat("qubyte").await;
+    chat.send_text("/new").await.unwrap();
+    chat.recv_update().await.unwrap();
+
+    let admin = fakeserver.create_chat("admin").await;
+    admin.send_text("/pause").await.unwrap();
+    assert!(admin
+        .recv_update()
+        .await
+        .unwrap()
+        .to_string()
+        .starts_with("New games are paused."));
+
+    chat.send_text("hello").await.unwrap();
+    assert!(chat
+        .recv_update()
+        .await
+        .unwrap()
+        .to_string()
+        .contains("You won"));
+
+    chat.send_text("/new").await.unwrap();
+    assert_eq!(
+        chat.recv_update().await.unwrap().to_string(),
+        "Sorry qubyte, BadWordle is down for maintenance. Please try again later."
+    );
+
+    admin.send_text("/resume").await.unwrap();
+    assert_eq!(
+        admin.recv_update().await.unwrap().to_string(),
+        "New games are resumed."
+    );
+
+    chat.send_text("/new").await.unwrap();
+    assert!(chat
+        .recv_update()
+        .await
+        .unwrap()
+        .to_string()
+        .starts_with("Hi qubyte"));
+
+    stop_bot(shutdown_notifier, shutdown_tx).await;
+}
+
+/// Each accepted attempt is saved, so a restart mid-game keeps the board.
+#[tokio::test]
+async fn attempts_saved_mid_game() {
+    for (name, delay) in [("attempts-saved", 0), ("attempts-saved-delayed", 60)] {
+        let save_dir = test_save_dir(name);
+        let user = test_user(42);
+
+        let mut app = App::new("BadWordle".into(), vec!["hello".to_string()]);
+        app.set_save_dir(save_dir.clone());
+        app.set_save_delay(Duration::from_secs(delay));
+        app.start_game().await.unwrap();
+        app.play_turn(&user, "world".into()).await.unwrap();
+        app.flush_saves().await.unwrap();
+
+        let mut app = App::new("BadWordle".into(), vec!["hello".to_string()]);
+        app.set_save_dir(save_dir);
+        app.load(&user).await.unwrap();
+        assert_eq!(app.wordle.as_ref().unwrap().attempts, vec!["WORLD"]);
+        assert!(app.is_playing());
+    }
+}
+
+/// Messages without a sender, e.g. anonymous channel posts, are refused rather
+/// than played as user 0.
+#[tokio::test]
+async fn messages_without_sender_refused() {
+    let save_dir = test_save_dir("no-sender");
+    let mut app = App::new("BadWordle".into(), vec!["hello".to_string()]);
+    app.set_save_dir(save_dir.clone());
+    let (fakeserver, shutdown_notifier, shutdown_tx) = start_bot(app);
+
+    let chat = fakeserver.create_chat("qubyte").await;
+    for text in ["/new", "hello"] {
+        chat.send_update(Update::Message(api::Message {
+            from: None,
+            chat: api::Chat {
+                id: chat.chat_id,
+                ..Default::default()
+            },
+            text: Some(text.into()),
+            ..Default::default()
+        }))
+        .await
+        .unwrap();
+        assert!(chat
+            .recv_update()
+            .await
+            .unwrap()
+            .to_string()
+            .starts_with("Sorry, I can't tell who sent that message"));
+    }
+
+    assert!(!std::path::Path::new(&format!("{}/0.json", save_dir)).exists());
+    stop_bot(shutdown_notifier, shutdown_tx).await;
+}
+
+/// A win in a group chat ends with a scoreboard comparing the winner to everyone
+/// who has played in the group.
+#[tokio::test]
+async fn group_win_scoreboard() {
+    let app = App::new("BadWordle".into(), vec!["hello".to_string()]);
+    let (fakeserver, shutdown_notifier, shutdown_tx) = start_bot(app);
+
+    let chat = fakeserver.create_chat("group").await;
+    let send = |id: i64, name: &str, text: &str| {
+        chat.send_update(Update::Message(api::Message {
+            from: Some(api::User {
+                id,
+                first_name: name.into(),
+                ..Default::default()
+            }),
+            chat: api::Chat {
+                id: chat.chat_id,
+                chat_type: "group".into(),
+                ..Default::default()
+            },
+            text: Some(text.into()),
+            ..Default::default()
+        }))
+    };
+
+    // Alice loses, then Bob wins.
+    send(1, "alice", "/new").await.unwrap();
+    chat.recv_update().await.unwrap();
+    for guess in ["world", "crane", "pious", "tryst", "jumbo", "knelt"] {
+        send(1, "alice", guess).await.unwrap();
+        chat.recv_update().await.unwrap();
+    }
+    send(2, "bob", "/new").await.unwrap();
+    chat.recv_update().await.unwrap();
+    send(2, "bob", "hello").await.unwrap();
+    let reply = chat.recv_update().await.unwrap().to_string();
+    assert!(reply.contains("You won"));
+    assert!(reply.ends_with("Scoreboard: bob 100% \\(1/1\\), group average 50% across 2 players"));
+
+    stop_bot(shutdown_notifier, shutdown_tx).await;
+}
+
+/// `/recent` lists the last few games, newest first, capped at RECENT_GAMES.
+#[tokio::test]
+async fn recent_command() {
+    let app = App::new("BadWordle".into(), vec!["hello".to_string()]);
+    let (fakeserver, shutdown_notifier, shutdown_tx) = start_bot(app);
+
+    let chat = fakeserver.create_chat("qubyte").await;
+    chat.send_text("/recent").await.unwrap();
+    assert_eq!(
+        chat.recv_update().await.unwrap().to_string(),
+        "You haven't finished any games yet."
+    );
+
+    let games: [&[&str]; 3] = [
+        &["hello"],
+        &["world", "world", "world", "world", "world", "world"],
+        &["world", "hello"],
+    ];
+    for guesses in games {
+        chat.send_text("/new").await.unwrap();
+        chat.recv_update().await.unwrap();
+        for guess in guesses {
+            chat.send_text(*guess).await.unwrap();
+            chat.recv_update().await.unwrap();
+        }
+    }
+
+    chat.send_text("/recent").await.unwrap();
+    assert_eq!(
+        chat.recv_update().await.unwrap().to_string(),
+        "Your last 3 games, newest first:\nHELLO: won in 2/6\nHELLO: lost\nHELLO: won in 1/6"
+    );
+
+    stop_bot(shutdown_notifier, shutdown_tx).await;
+
+    // Only the last RECENT_GAMES games are kept.
+    let mut app = App::new("BadWordle".into(), vec!["hello".to_string()]);
+    let user = test_user(42);
+    for _ in 0..RECENT_GAMES + 2 {
+        app.start_game().await.unwrap();
+        app.play_turn(&user, "hello".into()).await.unwrap();
+    }
+    assert_eq!(app.recent_games().len(), RECENT_GAMES);
+}
+
+/// Messages without text, e.g. photos, get a helpful reply.
+#[tokio::test]
+async fn messages_without_text() {
+    let app = App::new("BadWordle".into(), vec!["hello".to_string()]);
+    let (fakeserver, shutdown_notifier, shutdown_tx) = start_bot(app);
+
+    let chat = fakeserver.create_chat("qubyte").await;
+    chat.send_text("/new").await.unwrap();
+    chat.recv_update().await.unwrap();
+
+    chat.send_update(Update::Message(api::Message {
+        from: Some(test_user(chat.chat_id)),
+        chat: api::Chat {
+            id: chat.chat_id,
+            ..Default::default()
+        },
+        text: None,
+        photo: Some(vec![api::PhotoSize {
+            file_id: "photo".into(),
+            width: 1,
+            height: 1,
+            file_size: None,
+        }]),
+        ..Default::default()
+    }))
+    .await
+    .unwrap();
+    assert_eq!(
+        chat.recv_update().await.unwrap().to_string(),
+        "Sorry, I can only read text. Please send a word as text."
+    );
+
+    // The game goes on.
+    chat.send_text("hello").await.unwrap();
+    assert!(chat
+        .recv_update()
+        .await
+        .unwrap()
+        .to_string()
+        .contains("You won"));
+
+    stop_bot(shutdown_notifier, shutdown_tx).await;
+}
@@ -0,0 +1,54 @@
+/// Stats cards summarize a player's stats in a fixed-size box that's easy to
+/// screenshot and share. mobot can't upload generated images, so cards are drawn
+/// with box-drawing characters and sent as a monospaced code block.
+use crate::app::{format_percentage, Score};
+
+/// The width of a card, in characters, including the border.
+pub const CARD_WIDTH: usize = 24;
+
+/// The height of a card, in lines, including the border.
+pub const CARD_HEIGHT: usize = 6;
+
+/// Pads or truncates `text` to exactly `width` characters.
+fn fit(text: &str, width: usize) -> String {
+    let text = text.chars().take(width).collect::<String>();
+    format!("{:<width$}", text, width = width)
+}
+
+/// Renders the stats card for the named player. Below `min_games` games, the win
+/// rate is left out.
+pub fn render(name: &str, score: &Score, min_games: u32) -> String {
+    let inner = CARD_WIDTH - 4;
+    // Scores set by admins may have more wins than counted games, so the bar can't
+    // be trusted to stay within 100%.
+    let percent = score.win_rate().min(100.0) as usize;
+    let filled = percent * inner / 100;
+    let bar = "\u{2588}".repeat(filled) + &"\u{2591}".repeat(inner - filled);
+
+    let rows: [String; CARD_HEIGHT - 2] = [
+        fit(name, inner),
+        if score.games < min_games {
+            fit("Win rate -", inner)
+        } else {
+            fit(
+                &format!(
+                    "Win rate {}",
+                    format_percentage(score.wins, score.games.saturating_sub(score.forgiven), 0)
+                ),
+                inner,
+            )
+        },
+        bar,
+        fit(&format!("Won {} of {}", score.wins, score.games), inner),
+    ];
+
+    let mut card = format!("\u{250C}{}\u{2510}\n", "\u{2500}".repeat(CARD_WIDTH - 2));
+    for row in rows {
+        card.push_str(&format!("\u{2502} {} \u{2502}\n", row));
+    }
+    card.push_str(&format!(
+        "\u{2514}{}\u{2518}",
+        "\u{2500}".repeat(CARD_WIDTH - 2)
+    ));
+    card
+}
@@ -0,0 +1,76 @@
+use crate::app::Score;
+use crate::card::*;
+
+#[test]
+fn card_has_fixed_size() {
+    let score = Score {
+        games: 4,
+        wins: 3,
+        ..Default::default()
+    };
+    for name in ["q", "a very long name that doesn't fit on the card"] {
+        let card = render(name, &score, 0);
+        let lines = card.lines().collect::<Vec<_>>();
+        assert_eq!(lines.len(), CARD_HEIGHT);
+        assert!(lines.iter().all(|l| l.chars().count() == CARD_WIDTH));
+    }
+
+    let card = render("qubyte", &score, 0);
+    assert!(card.contains("Win rate 75%"));
+    assert!(card.contains("Won 3 of 4"));
+}
+
+#[test]
+fn card_without_games() {
+    let card = render("qubyte", &Score::default(), 0);
+    assert!(card.contains("Win rate 0%"));
+}
+
+#[test]
+fn card_with_more_wins_than_counted_games() {
+    for forgiven in [2, 4] {
+        let score = Score {
+            games: 4,
+            wins: 3,
+            forgiven,
+        };
+        let card = render("qubyte", &score, 0);
+        assert!(card.lines().all(|l| l.chars().count() == CARD_WIDTH));
+    }
+    assert_eq!(
+        Score {
+            games: 2,
+            wins: 0,
+            forgiven: 2
+        }
+        .win_rate(),
+        0.0
+    );
+}
+
+#[test]
+fn card_with_large_admin_set_score() {
+    let score = Score {
+        games: 4_000_000_000,
+        wins: 3_000_000_000,
+        forgiven: 0,
+    };
+    let card = render("qubyte", &score, 0);
+    assert!(card.lines().all(|l| l.chars().count() == CARD_WIDTH));
+    assert!(card.contains("Win rate 75%"), "{}", card);
+    assert_eq!(card.matches('\u{2588}').count(), 15);
+}
+
+#[test]
+fn card_hides_rate_below_min_games() {
+    let card = render(
+        "qubyte",
+        &Score {
+            games: 1,
+            wins: 1,
+            ..Default::default()
+        },
+        5,
+    );
+    assert!(card.contains("Win rate -"));
+}
@@ -0,0 +1,37 @@
+/// Challenges let players share a specific puzzle with friends. A challenge code
+/// encodes the target word so it can be passed around in a `/start <code>` deep
+/// link without giving the word away at a glance.
+use std::str;
+
+/// Key used to scramble the target word. This is obfuscation, not encryption.
+const KEY: &[u8] = b"wordlebot";
+
+/// Returns the challenge code for the given word.
+pub fn encode(word: &str) -> String {
+    word.to_uppercase()
+        .bytes()
+        .zip(KEY.iter().cycle())
+        .map(|(b, k)| format!("{:02x}", b ^ k))
+        .collect()
+}
+
+/// Returns the word encoded in the given challenge code, or `None` if the code
+/// is not valid.
+pub fn decode(code: &str) -> Option<String> {
+    let bytes = code
+        .as_bytes()
+        .chunks(2)
+        .map(|pair| match pair {
+            [hi, lo] => u8::from_str_radix(str::from_utf8(&[*hi, *lo]).ok()?, 16).ok(),
+            _ => None,
+        })
+        .collect::<Option<Vec<_>>>()?
+        .into_iter()
+        .zip(KEY.iter().cycle())
+        .map(|(b, k)| b ^ k)
+        .collect::<Vec<_>>();
+
+    String::from_utf8(bytes)
+        .ok()
+        .filter(|word| !word.is_empty() && word.chars().all(char::is_alphabetic))
+}
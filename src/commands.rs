@@ -0,0 +1,169 @@
+/// The registry of user-facing bot commands. It's the single source of truth for
+/// both the Telegram command menu and the /help text, so the two can't drift.
+use mobot::api;
+
+/// CommandSpec describes a single bot command.
+#[derive(Debug, Clone)]
+pub struct CommandSpec {
+    /// The command, including the leading slash, e.g. "/new".
+    pub command: &'static str,
+
+    /// A short description, shown in the Telegram menu and in /help.
+    pub description: &'static str,
+}
+
+/// Returns all user-facing commands, in menu order. Admin commands are left out.
+pub fn registry() -> Vec<CommandSpec> {
+    vec![
+        CommandSpec {
+            command: "/help",
+            description: "Show help",
+        },
+        CommandSpec {
+            command: "/new",
+            description: "New game",
+        },
+        CommandSpec {
+            command: "/giveup",
+            description: "Give up my game",
+        },
+        CommandSpec {
+            command: "/score",
+            description: "Show my score",
+        },
+        CommandSpec {
+            command: "/leaderboard",
+            description: "Show the top players",
+        },
+        CommandSpec {
+            command: "/rank",
+            description: "Show my rank by win rate",
+        },
+        CommandSpec {
+            command: "/card",
+            description: "Show my stats card",
+        },
+        CommandSpec {
+            command: "/replay",
+            description: "Show my last finished game",
+        },
+        CommandSpec {
+            command: "/recent",
+            description: "Show my last few games",
+        },
+        CommandSpec {
+            command: "/grid",
+            description: "Show the share grid of my game so far",
+        },
+        CommandSpec {
+            command: "/challenge",
+            description: "Challenge a friend to my word",
+        },
+        CommandSpec {
+            command: "/validinfo",
+            description: "Check which words are valid",
+        },
+        CommandSpec {
+            command: "/possible",
+            description: "Count the words still possible",
+        },
+        CommandSpec {
+            command: "/freqhint",
+            description: "Hint at the letters of the word",
+        },
+        CommandSpec {
+            command: "/describe",
+            description: "Describe my board in words",
+        },
+        CommandSpec {
+            command: "/spoilers",
+            description: "Hide wrong letters behind spoilers, on or off",
+        },
+        CommandSpec {
+            command: "/plainletters",
+            description: "Show correct letters as plain letters, on or off",
+        },
+        CommandSpec {
+            command: "/assist",
+            description: "Reveal a letter halfway through a game, on or off",
+        },
+        CommandSpec {
+            command: "/terse",
+            description: "Leave the comments out of replies, on or off",
+        },
+        CommandSpec {
+            command: "/tone",
+            description: "Make the bot polite or rude",
+        },
+        CommandSpec {
+            command: "/analytics",
+            description: "Share my games with the admin, on or off",
+        },
+        CommandSpec {
+            command: "/reminders",
+            description: "Get a nudge about unfinished games, on or off",
+        },
+        CommandSpec {
+            command: "/length",
+            description: "Choose the word length",
+        },
+        CommandSpec {
+            command: "/tries",
+            description: "Choose how many guesses you get",
+        },
+        CommandSpec {
+            command: "/settings",
+            description: "Show my settings",
+        },
+        CommandSpec {
+            command: "/feedback",
+            description: "Send feedback to the bot admin",
+        },
+        CommandSpec {
+            command: "/verify",
+            description: "Check that a shared grid is consistent",
+        },
+    ]
+}
+
+/// Returns the registry as Telegram bot commands, for `set_my_commands`.
+pub fn bot_commands() -> Vec<api::BotCommand> {
+    registry()
+        .iter()
+        .map(|spec| api::BotCommand {
+            command: spec.command.into(),
+            description: spec.description.into(),
+        })
+        .collect()
+}
+
+/// Returns the /help text as MarkdownV2, listing every registered command. If the
+/// target words are all one length, the text says how long. `max_attempts` is the
+/// player's number of guesses.
+pub fn help_text(game_name: &str, word_lengths: &[usize], max_attempts: usize) -> String {
+    let goal = match word_lengths {
+        [len] => format!(
+            "Guess the {}\\-letter word within {} tries\\.",
+            len, max_attempts
+        ),
+        [] => format!(
+            "The goal of the game is to guess the target word within {} tries\\.",
+            max_attempts
+        ),
+        _ => format!(
+            "Guess the word within {} tries\\. Word lengths vary, so pick one with /length\\.",
+            max_attempts
+        ),
+    };
+    let mut help = format!("Welcome to {}\\! {}\n\n", api::escape_md(game_name), goal);
+
+    for spec in registry() {
+        help.push_str(&format!(
+            "{} \\- {}\n",
+            api::escape_md(spec.command),
+            api::escape_md(spec.description)
+        ));
+    }
+
+    help
+}
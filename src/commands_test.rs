@@ -0,0 +1,44 @@
+use crate::commands::*;
+use mobot::api;
+
+#[test]
+fn help_lists_every_command() {
+    let help = help_text("BadWordle", &[5], 6);
+    assert!(help.starts_with("Welcome to BadWordle\\!"));
+
+    for spec in registry() {
+        assert!(
+            help.contains(&format!(
+                "{} \\- {}",
+                api::escape_md(spec.command),
+                api::escape_md(spec.description)
+            )),
+            "{} is missing from /help",
+            spec.command
+        );
+    }
+}
+
+/// Markdown in the game name is escaped, so it can't break the help message.
+#[test]
+fn help_escapes_game_name() {
+    let help = help_text("*Bad_Wordle* [x](y)", &[5], 6);
+    assert!(help.starts_with("Welcome to \\*Bad\\_Wordle\\* \\[x\\]\\(y\\)\\!"));
+}
+
+/// The help says how long the word is, unless the target lengths vary.
+#[test]
+fn help_mentions_word_length() {
+    assert!(help_text("BadWordle", &[5], 6).contains("Guess the 5\\-letter word"));
+
+    let help = help_text("BadWordle", &[5, 6], 6);
+    assert!(help.contains("Word lengths vary"));
+    assert!(!help.contains("letter word"));
+}
+
+/// The help gives the player's own number of guesses.
+#[test]
+fn help_mentions_max_attempts() {
+    assert!(help_text("BadWordle", &[5], 4).contains("within 4 tries"));
+    assert!(help_text("BadWordle", &[5, 6], 10).contains("within 10 tries"));
+}
@@ -0,0 +1,123 @@
+/// Share grids are the rows of colored squares players paste to show off a game
+/// without giving the word away. This module reads them back, so a pasted grid can
+/// be checked for consistency. The squares don't say which letters were played, so
+/// parsed letters are all `?`.
+use crate::wordle::Letter;
+
+/// The letter used for the unknown letters of a parsed grid.
+pub const UNKNOWN: char = '?';
+
+/// GridError is why a pasted grid can't be read, or isn't consistent.
+#[derive(Debug, PartialEq)]
+pub enum GridError {
+    /// There are no rows of squares.
+    Empty,
+
+    /// A row has something other than squares in it. Rows are numbered from 1.
+    NotSquares(usize),
+
+    /// A row has a different number of squares than the first row.
+    Ragged(usize),
+
+    /// There are rows after the word was solved.
+    RowsAfterWin(usize),
+
+    /// A square that was correct isn't correct in a later row, which hard mode
+    /// doesn't allow.
+    GreenDropped { row: usize, column: usize },
+}
+
+impl std::fmt::Display for GridError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GridError::Empty => write!(f, "there are no rows of squares"),
+            GridError::NotSquares(row) => write!(f, "row {} isn't all squares", row),
+            GridError::Ragged(row) => {
+                write!(f, "row {} doesn't have as many squares as row 1", row)
+            }
+            GridError::RowsAfterWin(row) => {
+                write!(f, "row {} comes after the word was solved", row)
+            }
+            GridError::GreenDropped { row, column } => write!(
+                f,
+                "square {} of row {} is no longer green, which hard mode doesn't allow",
+                column, row
+            ),
+        }
+    }
+}
+
+/// Returns the assessment a square stands for. Both the standard and the high
+/// contrast colors are accepted, and either a black or a white square for a wrong
+/// letter.
+fn square(c: char) -> Option<Letter> {
+    match c {
+        '\u{1F7E9}' | '\u{1F7E7}' => Some(Letter::Correct(UNKNOWN)),
+        '\u{1F7E8}' | '\u{1F7E6}' => Some(Letter::CorrectButWrongPosition(UNKNOWN)),
+        '\u{2B1B}' | '\u{2B1C}' => Some(Letter::Wrong(UNKNOWN)),
+        _ => None,
+    }
+}
+
+/// `parse` reads the rows of a pasted grid. Lines without squares, like the
+/// "Wordle 1,234 4/6" header, are skipped, but a line with squares must be nothing
+/// but squares.
+pub fn parse(text: &str) -> Result<Vec<Vec<Letter>>, GridError> {
+    let mut rows = vec![];
+    for line in text.lines() {
+        // Some platforms add a variation selector after the black and white squares.
+        let squares = line
+            .chars()
+            .filter(|c| !c.is_whitespace() && *c != '\u{FE0F}')
+            .collect::<Vec<_>>();
+        if !squares.iter().any(|c| square(*c).is_some()) {
+            continue;
+        }
+
+        let row = squares
+            .into_iter()
+            .map(square)
+            .collect::<Option<Vec<_>>>()
+            .ok_or(GridError::NotSquares(rows.len() + 1))?;
+        rows.push(row);
+    }
+
+    if rows.is_empty() {
+        return Err(GridError::Empty);
+    }
+    Ok(rows)
+}
+
+/// `verify` checks that the rows could come from one game: they're all the same
+/// width, and nothing follows a solved row. With `hard_mode`, squares that were
+/// correct must stay correct.
+pub fn verify(rows: &[Vec<Letter>], hard_mode: bool) -> Result<(), GridError> {
+    let Some(first) = rows.first() else {
+        return Err(GridError::Empty);
+    };
+
+    for (i, row) in rows.iter().enumerate() {
+        if row.len() != first.len() {
+            return Err(GridError::Ragged(i + 1));
+        }
+
+        if i > 0 && rows[i - 1].iter().all(|l| matches!(l, Letter::Correct(_))) {
+            return Err(GridError::RowsAfterWin(i + 1));
+        }
+
+        if !hard_mode || i == 0 {
+            continue;
+        }
+
+        for (column, (before, after)) in rows[i - 1].iter().zip(row).enumerate() {
+            if matches!(before, Letter::Correct(_)) && !matches!(after, Letter::Correct(_)) {
+                return Err(GridError::GreenDropped {
+                    row: i + 1,
+                    column: column + 1,
+                });
+            }
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,59 @@
+use crate::grid::*;
+use crate::wordle::Letter;
+
+#[test]
+fn parses_shared_grid() {
+    use Letter::*;
+
+    let rows = parse(
+        "Wordle 1,234 3/6\n\n\u{2B1B}\u{FE0F}\u{1F7E8}\u{2B1B}\u{2B1B}\u{1F7E9}\n\
+         \u{1F7E9}\u{2B1C}\u{1F7E8}\u{2B1B}\u{1F7E9}\n\
+         \u{1F7E9}\u{1F7E9}\u{1F7E9}\u{1F7E9}\u{1F7E9}\n",
+    )
+    .unwrap();
+
+    assert_eq!(
+        rows,
+        vec![
+            vec![
+                Wrong('?'),
+                CorrectButWrongPosition('?'),
+                Wrong('?'),
+                Wrong('?'),
+                Correct('?')
+            ],
+            vec![
+                Correct('?'),
+                Wrong('?'),
+                CorrectButWrongPosition('?'),
+                Wrong('?'),
+                Correct('?')
+            ],
+            vec![Correct('?'); 5],
+        ]
+    );
+    assert_eq!(verify(&rows, true), Ok(()));
+}
+
+#[test]
+fn rejects_malformed_grids() {
+    assert_eq!(parse("Wordle 1,234 X/6"), Err(GridError::Empty));
+    assert_eq!(
+        parse("\u{1F7E9}\u{1F7E9}\n\u{1F7E9}x\u{1F7E9}"),
+        Err(GridError::NotSquares(2))
+    );
+
+    let ragged = parse("\u{2B1B}\u{2B1B}\u{2B1B}\n\u{1F7E9}\u{1F7E9}").unwrap();
+    assert_eq!(verify(&ragged, false), Err(GridError::Ragged(2)));
+
+    let after_win = parse("\u{1F7E9}\u{1F7E9}\n\u{1F7E9}\u{1F7E9}").unwrap();
+    assert_eq!(verify(&after_win, false), Err(GridError::RowsAfterWin(2)));
+
+    // Dropping a green is only inconsistent in hard mode.
+    let dropped = parse("\u{1F7E9}\u{2B1B}\n\u{2B1B}\u{1F7E8}").unwrap();
+    assert_eq!(verify(&dropped, false), Ok(()));
+    assert_eq!(
+        verify(&dropped, true),
+        Err(GridError::GreenDropped { row: 2, column: 1 })
+    );
+}
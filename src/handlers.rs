@@ -1,11 +1,20 @@
-use std::sync::Arc;
+use std::{
+    fmt::Display,
+    sync::Arc,
+    time::{Duration, Instant},
+};
 
-use anyhow::anyhow;
 use log::*;
-use mobot::api::escape_md;
+use mobot::api::{escape_md, User};
 use mobot::*;
 
 use crate::app::*;
+use crate::card;
+use crate::challenge;
+use crate::commands;
+use crate::grid;
+use crate::leaderboard;
+use crate::season;
 use crate::wordle;
 
 /// emoji_letter takes a capital letter and returns the corresponding emoji letter
@@ -18,155 +27,1655 @@ fn emoji_letter(l: char) -> char {
     std::char::from_u32(base + target - a).unwrap_or('?')
 }
 
-/// render_game takes a game::Game and returns a string representation of it.
+/// Comments on a valid guess that didn't win, indexed by the number of attempts
+/// remaining. The fewer attempts left, the ruder the bot gets.
+const RUDE_COMMENTS: [&str; 6] = [
+    "Out of guesses.",
+    "One guess left. Don't embarrass yourself. \u{1F62C}",
+    "Are you even trying? \u{1F926} Guess again.",
+    "Is that really your best? \u{1F644} Guess another word.",
+    "Hmm. Guess another word?",
+    "Nice try. Guess another word?",
+];
+
+/// try_again_comment returns the comment for a valid guess that didn't win, given the
+/// number of attempts remaining. Polite players always get the gentlest comment.
+pub fn try_again_comment(remaining: usize, polite: bool) -> &'static str {
+    let last = RUDE_COMMENTS.len() - 1;
+    if polite {
+        return RUDE_COMMENTS[last];
+    }
+
+    RUDE_COMMENTS[remaining.min(last)]
+}
+
+/// celebration_for returns the comment on a win in `guesses` of `max` attempts. A
+/// first-guess win gets the most excitement, and a last-guess win sounds relieved.
+/// Polite players get kinder words.
+pub fn celebration_for(guesses: usize, max: usize, polite: bool) -> &'static str {
+    match (guesses, polite) {
+        (1, false) => "First try?! Did you peek?",
+        (1, true) => "First try! Amazing!",
+        (g, false) if g >= max => "Phew. That was close, don't make me sweat like that.",
+        (g, true) if g >= max => "Phew, just in time!",
+        (_, false) => "Not bad, for once.",
+        (_, true) => "Well played!",
+    }
+}
+
+/// without_emoji drops the words of a comment that aren't ASCII, like the emoji of
+/// the rude comments.
+fn without_emoji(comment: &str) -> String {
+    comment
+        .split_whitespace()
+        .filter(|word| word.is_ascii())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// UserError is a recoverable problem with an incoming message. Instead of failing,
+/// handlers reply with a friendly explanation.
+#[derive(Debug, PartialEq)]
+pub enum UserError {
+    /// The message has no text, e.g. a photo or a file.
+    NoText,
+
+    /// The message has no sender, e.g. an anonymous channel post.
+    NoUser,
+}
+
+impl Display for UserError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            UserError::NoText => write!(
+                f,
+                "Sorry, I can only read text. Please send a word as text."
+            ),
+            UserError::NoUser => write!(
+                f,
+                "Sorry, I can't tell who sent that message, so I can't play here. \
+                 Anonymous posts, e.g. from channel admins, aren't supported."
+            ),
+        }
+    }
+}
+
+impl std::error::Error for UserError {}
+
+/// reply_to_user_errors turns user errors into replies, and passes everything else
+/// through.
+pub fn reply_to_user_errors(
+    result: Result<Action, anyhow::Error>,
+) -> Result<Action, anyhow::Error> {
+    match result {
+        Err(err) => match err.downcast_ref::<UserError>() {
+            Some(user_error) => {
+                warn!("Replying to user error: {}", user_error);
+                Ok(Action::ReplyText(user_error.to_string()))
+            }
+            None => Err(err),
+        },
+        result => result,
+    }
+}
+
+/// message_text returns the text of the message.
+pub fn message_text(message: &api::Message) -> Result<String, UserError> {
+    message.text.clone().ok_or(UserError::NoText)
+}
+
+/// Quote marks that keyboards and copy-paste wrap words in.
+const QUOTES: &[char] = &[
+    '\'', '"', '`', '\u{2018}', '\u{2019}', '\u{201C}', '\u{201D}',
+];
+
+/// strip_quotes removes quote marks wrapped around a guess, so `"crane"` plays as
+/// `crane`. Only matched pairs at both ends are removed, so apostrophes inside or at
+/// one end of a word are kept.
+pub fn strip_quotes(guess: &str) -> &str {
+    let mut guess = guess.trim();
+    while guess.chars().count() >= 2 && guess.starts_with(QUOTES) && guess.ends_with(QUOTES) {
+        let first = guess.chars().next().unwrap();
+        let last = guess.chars().next_back().unwrap();
+        guess = guess[first.len_utf8()..guess.len() - last.len_utf8()].trim();
+    }
+    guess
+}
+
+/// sender returns the user who sent the event's message.
+fn sender(e: &Event) -> Result<&User, UserError> {
+    e.update.from_user().map_err(|_| UserError::NoUser)
+}
+
+/// is_group_chat returns true if the event's message was sent in a group.
+fn is_group_chat(e: &Event) -> bool {
+    e.update
+        .get_message()
+        .is_ok_and(|m| matches!(m.chat.chat_type.as_str(), "group" | "supergroup"))
+}
+
+/// parse_chat_id reads a chat or user ID from an admin command, with an error that
+/// can be shown to the admin.
+pub fn parse_chat_id(text: &str) -> anyhow::Result<i64> {
+    let text = text.trim();
+    if text.is_empty() {
+        anyhow::bail!("a chat ID is needed");
+    }
+
+    match text.parse::<i64>() {
+        Ok(0) => anyhow::bail!("0 is not a chat ID"),
+        Ok(chat_id) => Ok(chat_id),
+        Err(e)
+            if matches!(
+                e.kind(),
+                std::num::IntErrorKind::PosOverflow | std::num::IntErrorKind::NegOverflow
+            ) =>
+        {
+            anyhow::bail!("{} is too large for a chat ID", text)
+        }
+        Err(_) => anyhow::bail!("{:?} is not a chat ID, which is a number like 12345", text),
+    }
+}
+
+/// logged_target returns the target word as it should appear in admin logs.
+fn logged_target(app: &App, target_word: &str) -> String {
+    if app.hide_targets() {
+        "(hidden)".into()
+    } else {
+        target_word.into()
+    }
+}
+
+/// revealed_target returns how a lost game's target word is shown to the player.
+/// If words are hidden on loss, the word is held back for /reveal instead.
+async fn revealed_target(e: &Event, app: &App, target_word: &str) -> Result<String, anyhow::Error> {
+    if !app.hide_word_on_loss() {
+        return Ok(format!("Target word: {}", target_word));
+    }
+
+    app.hold_back_word(e.update.chat_id()?, target_word).await;
+    Ok("The word will be revealed when the puzzle closes.".into())
+}
+
+/// render_letter returns the Markdown representation of a single assessed letter. If
+/// `spoilers` is false, wrong letters are struck through without spoiler markup. If
+/// `plain_letters` is set, correct letters are bracketed monospace instead of emoji.
+///
+/// Emoji letters are regional indicators, and clients draw two adjacent ones as a
+/// flag, e.g. U and S as the US flag. Each is preceded by a space to keep them apart.
+fn render_letter(letter: &wordle::Letter, spoilers: bool, plain_letters: bool) -> String {
+    match letter {
+        wordle::Letter::Correct(c) if plain_letters => format!(" `[{}]` ", c),
+        wordle::Letter::Correct(c) => format!(" {}", emoji_letter(*c)),
+        wordle::Letter::CorrectButWrongPosition(c) => format!(" * `{}` *  ", c),
+        wordle::Letter::Wrong(c) if spoilers => format!(" || ~{}~ ||  ", c),
+        wordle::Letter::Wrong(c) => format!(" ~{}~  ", c),
+    }
+}
+
+/// render_board takes a game::Game and returns a string representation of its
+/// attempts, without the legend of `render_game`.
 /// Emoji codepoints: https://emojipedia.org/emoji/
-fn render_game(game: &wordle::Game) -> String {
+pub fn render_board(game: &wordle::Game, spoilers: bool, plain_letters: bool) -> String {
     let mut s = String::from("Your attempts:\n\n");
     for attempt in &game.attempts {
         for letter in attempt {
-            match letter {
-                wordle::Letter::Correct(c) => {
-                    s.push_str(&format!(" {}", emoji_letter(*c).to_string()))
+            s.push_str(&render_letter(letter, spoilers, plain_letters));
+        }
+        s.push_str("\n\n");
+    }
+    s
+}
+
+/// render_game takes a game::Game and returns a string representation of it. The
+/// first board of a game also explains what the glyphs mean.
+pub fn render_game(game: &wordle::Game, spoilers: bool, plain_letters: bool) -> String {
+    let mut s = render_board(game, spoilers, plain_letters);
+    if game.attempts.len() == 1 {
+        s.push_str(&format!(
+            "{} right spot \\| {} wrong spot \\| {} not in the word\n",
+            render_letter(&wordle::Letter::Correct('A'), spoilers, plain_letters),
+            render_letter(
+                &wordle::Letter::CorrectButWrongPosition('A'),
+                spoilers,
+                plain_letters
+            ),
+            render_letter(&wordle::Letter::Wrong('A'), spoilers, plain_letters),
+        ));
+    }
+    s
+}
+
+/// letter_hint describes how many different letters the target word has, and whether
+/// any of them repeats, without giving away which.
+pub fn letter_hint(target_word: &str) -> String {
+    let mut letters = target_word.to_uppercase().chars().collect::<Vec<_>>();
+    let len = letters.len();
+    letters.sort();
+    letters.dedup();
+
+    format!(
+        "The word has {} different letters, and {}.",
+        letters.len(),
+        if letters.len() < len {
+            "at least one of them repeats"
+        } else {
+            "none of them repeats"
+        }
+    )
+}
+
+/// describe_game returns a plain-text description of the board, one line per
+/// attempt followed by the letters tried in alphabetical order, for players using
+/// screen readers.
+pub fn describe_game(game: &wordle::Game) -> String {
+    if game.attempts.is_empty() {
+        return "No guesses yet.".into();
+    }
+
+    let tried = game
+        .attempted_letters()
+        .iter()
+        .map(|c| c.to_string())
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    let guesses = game
+        .attempts
+        .iter()
+        .enumerate()
+        .map(|(i, attempt)| {
+            let letters = attempt
+                .iter()
+                .map(|letter| match letter {
+                    wordle::Letter::Correct(c) => format!("{} correct", c),
+                    wordle::Letter::CorrectButWrongPosition(c) => {
+                        format!("{} present elsewhere", c)
+                    }
+                    wordle::Letter::Wrong(c) => format!("{} absent", c),
+                })
+                .collect::<Vec<_>>()
+                .join(", ");
+            format!("Guess {}: {}.", i + 1, letters)
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    format!("{}\nLetters tried: {}.", guesses, tried)
+}
+
+/// render_share_grid returns a spoiler-free grid of colored squares for the game,
+/// one row per attempt, suitable for sharing.
+pub fn render_share_grid(game: &wordle::Game) -> String {
+    game.attempts
+        .iter()
+        .map(|attempt| {
+            attempt
+                .iter()
+                .map(|letter| match letter {
+                    wordle::Letter::Correct(_) => '\u{1F7E9}',
+                    wordle::Letter::CorrectButWrongPosition(_) => '\u{1F7E8}',
+                    wordle::Letter::Wrong(_) => '\u{2B1B}',
+                })
+                .collect::<String>()
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// greeting_for returns the greeting for the given hour of the day (0-23).
+pub fn greeting_for(hour: u32) -> &'static str {
+    match hour {
+        5..=11 => "Good morning",
+        12..=17 => "Good afternoon",
+        _ => "Good evening",
+    }
+}
+
+pub async fn handle_new_game(e: Event, state: State<App>) -> Result<Action, anyhow::Error> {
+    Ok(Action::ReplyText(new_game(&e, &state, None).await?))
+}
+
+/// new_game starts a new game for the sender and returns the welcome message. If a
+/// challenge word is given, it's used as the target.
+async fn new_game(
+    e: &Event,
+    state: &State<App>,
+    challenge: Option<String>,
+) -> Result<String, anyhow::Error> {
+    // Get the sender's first name
+    let from = sender(e)?.clone();
+
+    // Get the application state
+    let mut app = state.get().write().await;
+    if app.is_paused().await {
+        return Ok(format!(
+            "Sorry {}, {} is down for maintenance. Please try again later.",
+            from.first_name, app.game_name
+        ));
+    }
+
+    if let Err(e) = app.load(&from).await {
+        warn!("No saved game state: {}", e);
+    }
+
+    let started = match challenge {
+        Some(word) => app.start_game_with(word).await,
+        None => app.start_game().await,
+    };
+
+    let target_word = match started {
+        Ok(target_word) => target_word,
+        Err(e) => {
+            warn!("Could not start game: {}", e);
+            return Ok(format!("Sorry {}, {}.", from.first_name, e));
+        }
+    };
+    app.inc_games(&from).await; // saves state
+    app.track_game(e.update.chat_id()?, &from).await;
+
+    info!(
+        "Starting new game with {} ({}), target word: {}.",
+        from.first_name,
+        from.username.clone().unwrap_or("unknown".into()),
+        target_word
+    );
+
+    app.admin_log(
+        Arc::clone(&e.api),
+        format!(
+            "{} ({}) starting a new game with word {}.",
+            from.first_name,
+            from.username.clone().unwrap_or_default(),
+            logged_target(&app, &target_word),
+        ),
+    )
+    .await;
+
+    let first_game = if app.score(&from.id.to_string()).await.games == 0 {
+        "This is your first game.".to_string()
+    } else {
+        format!(
+            "Your score: {}.",
+            format_score(
+                &app.score(&from.id.to_string()).await,
+                app.min_games_for_rate()
+            )
+        )
+    };
+
+    // Greet by time of day if the bot knows its timezone.
+    let greeting = match app.utc_offset() {
+        Some(offset) => {
+            let hour = (season::now() as i64 / 3600 + offset as i64).rem_euclid(24);
+            greeting_for(hour as u32)
+        }
+        None => "Hi",
+    };
+
+    Ok(format!(
+        "{} {}, Welcome to {}!\n\n{}\nGuess the {}-letter word.",
+        greeting,
+        from.first_name,
+        app.game_name,
+        first_game,
+        target_word.len()
+    ))
+}
+
+/// log_update logs the incoming update as JSON, if enabled with --debug-updates.
+async fn log_update(e: &Event, state: &State<App>) {
+    if !state.get().read().await.debug_updates() {
+        return;
+    }
+
+    info!("{}", format_update(&e.update));
+}
+
+/// format_update formats an incoming update for the debug log. Messages are logged as
+/// JSON, and other updates as they're debug printed.
+pub fn format_update(update: &Update) -> String {
+    match update.get_message() {
+        Ok(message) => format!(
+            "Update: {}",
+            serde_json::to_string(message).unwrap_or_default()
+        ),
+        Err(_) => format!("Update: {:?}", update),
+    }
+}
+
+pub async fn handle_bot_command(e: Event, state: State<App>) -> Result<Action, anyhow::Error> {
+    log_update(&e, &state).await;
+    restore_game(&e, &state).await;
+    let action = reply_to_user_errors(bot_command(e, state.clone()).await);
+    release_game(&state).await;
+    action
+}
+
+/// restore_game reloads the sender's game if the chat released it after its last
+/// message, for handlers that use the chat's game without loading it.
+async fn restore_game(e: &Event, state: &State<App>) {
+    let mut app = state.get().write().await;
+    if !app.take_released() {
+        return;
+    }
+
+    let Ok(from) = sender(e) else {
+        return;
+    };
+    if let Err(e) = app.load(from).await {
+        warn!("No saved game state: {}", e);
+    }
+}
+
+/// release_game drops the chat's game from its state once the message is handled,
+/// if games are capped with --max-games. mobot keeps every chat's state, so this is
+/// what bounds the games in memory.
+async fn release_game(state: &State<App>) {
+    let mut app = state.get().write().await;
+    if app.releases_games() {
+        app.release_game();
+    }
+}
+
+async fn bot_command(e: Event, state: State<App>) -> Result<Action, anyhow::Error> {
+    // Get the command
+    let text = message_text(e.update.get_message()?)?;
+
+    // Split the command from its arguments, e.g. "/length 6". Arguments may also
+    // start on the next line, like a grid pasted after /verify.
+    let (command, args) = text
+        .split_once(char::is_whitespace)
+        .unwrap_or((text.as_str(), ""));
+    let args = args.trim();
+    state
+        .get()
+        .write()
+        .await
+        .set_after_score(command == "/score");
+
+    let reply = match command {
+        "/help" => {
+            // Load the sender's settings, for their number of guesses.
+            let mut app = state.get().write().await;
+            if let Err(e) = app.load(sender(&e)?).await {
+                warn!("No saved game state: {}", e);
+            }
+            return Ok(Action::ReplyMarkdown(commands::help_text(
+                &app.game_name,
+                &app.word_lengths(),
+                app.max_attempts(),
+            )));
+        }
+
+        // Plain /new and /start go back to the default target words.
+        "/new" => {
+            state.get().write().await.set_mode(None)?;
+            return handle_new_game(e, state).await;
+        }
+
+        // Game modes, e.g. "/new7" for a game with its own target words.
+        _ if command.len() > "/new".len() && command.starts_with("/new") => {
+            let mode = command["/new".len()..].to_string();
+            if let Err(err) = state.get().write().await.set_mode(Some(mode)) {
+                return Ok(Action::ReplyText(format!("Sorry, {}.", err)));
+            }
+            return handle_new_game(e, state).await;
+        }
+
+        "/start" if args.is_empty() => {
+            state.get().write().await.set_mode(None)?;
+            return handle_new_game(e, state).await;
+        }
+
+        // Deep links to a challenge, e.g. "/start 1f2e3d".
+        "/start" => match challenge::decode(args) {
+            Some(word) => return Ok(Action::ReplyText(new_game(&e, &state, Some(word)).await?)),
+            None => "Sorry, that challenge is not valid.".into(),
+        },
+
+        "/giveup" => return handle_give_up(&e, &state).await,
+
+        "/challenge" => {
+            let app = state.get().read().await;
+            match &app.wordle {
+                Some(wordle) => format!(
+                    "Challenge your friends to this word! Send them: /start {}",
+                    challenge::encode(&wordle.target_word)
+                ),
+                None => "Start a game with /new first.".into(),
+            }
+        }
+
+        "/admin" => {
+            let mut app = state.get().write().await;
+            if app.admin_user.is_none() {
+                "Admin functions are disabled.".into()
+            } else if app
+                .auth_admin(
+                    sender(&e)?.username.clone().unwrap_or_default().as_str(),
+                    e.update.chat_id()?,
+                )
+                .await?
+            {
+                "Admin messages routed to this chat.".into()
+            } else {
+                "You are not an admin.".into()
+            }
+        }
+
+        "/score" => {
+            let from = sender(&e)?.clone();
+            let mut app = state.get().write().await;
+
+            // Get the application state
+            if let Err(e) = app.load(&from).await {
+                warn!("No saved game state: {}", e);
+                "You have not played any games yet.".into()
+            } else if app.has_seasons() {
+                format!(
+                    "Your score: {}\nThis season: {}",
+                    format_score(
+                        &app.score(&from.id.to_string()).await,
+                        app.min_games_for_rate()
+                    ),
+                    format_score(
+                        &app.season_score(&from.id.to_string()).await,
+                        app.min_games_for_rate()
+                    )
+                )
+            } else {
+                format!(
+                    "Your score: {}",
+                    format_score(
+                        &app.score(&from.id.to_string()).await,
+                        app.min_games_for_rate()
+                    )
+                )
+            }
+        }
+
+        "/length" => handle_length(&e, &state, args).await?,
+
+        "/tries" => handle_tries(&e, &state, args).await?,
+
+        "/validinfo" => {
+            let app = state.get().read().await;
+            if !args.is_empty() {
+                let word = args.to_uppercase();
+                if app.is_valid_word(word.clone()) {
+                    format!("{} is valid.", word)
+                } else {
+                    format!("{} is not valid.", word)
                 }
-                wordle::Letter::CorrectButWrongPosition(c) => {
-                    s.push_str(&format!(" * `{}` *  ", c))
+            } else if let Some(example) = app.random_valid_word() {
+                format!(
+                    "{} valid words loaded, e.g. {}. Type /validinfo WORD to check a word.",
+                    app.valid_word_count(),
+                    example.to_uppercase()
+                )
+            } else {
+                "Words are not validated, so any word is accepted.".into()
+            }
+        }
+
+        "/peek" => handle_peek(&e, &state, args).await?,
+
+        "/listwords" => handle_listwords(&e, &state, args).await?,
+
+        "/setscore" => handle_setscore(&e, &state, args).await?,
+
+        "/reload" => handle_reload(&e, &state).await?,
+
+        "/status" => handle_status(&e, &state).await?,
+
+        "/pause" | "/resume" => {
+            let app = state.get().read().await;
+            if app.is_admin(sender(&e)?) {
+                let paused = command == "/pause";
+                app.set_paused(paused).await;
+                if paused {
+                    "New games are paused. Games in progress can still be finished.".into()
+                } else {
+                    "New games are resumed.".into()
                 }
-                wordle::Letter::Wrong(c) => {
-                    s.push_str(&format!(" || ~{}~ ||  ", c))
-                    // s.push_str("\u{2796} ")
+            } else {
+                "You are not an admin.".into()
+            }
+        }
+
+        "/active" => {
+            let app = state.get().read().await;
+            if app.is_admin(sender(&e)?) {
+                format!("Games in progress: {}", app.active_games().await)
+            } else {
+                "You are not an admin.".into()
+            }
+        }
+
+        "/testword" => handle_test_word(&e, &state, args).await?,
+
+        "/solve" => handle_solve(&e, &state, args).await?,
+
+        "/reveal" => {
+            let app = state.get().read().await;
+            if app.is_admin(sender(&e)?) {
+                let chats = app.reveal_words(Arc::clone(&e.api)).await;
+                format!("Revealed the held back words to {} chats.", chats)
+            } else {
+                "You are not an admin.".into()
+            }
+        }
+
+        "/leaderboard" => handle_leaderboard(&state).await?,
+
+        "/rank" => handle_rank(&e, &state).await?,
+
+        "/exportcsv" => return handle_export_csv(&e, &state).await,
+
+        "/wordstats" => handle_word_stats(&e, &state, args).await?,
+
+        "/season" => handle_season(&e, &state).await?,
+
+        "/replay" => return handle_replay(&e, &state).await,
+
+        "/recent" => handle_recent(&e, &state).await?,
+
+        "/card" => {
+            let from = sender(&e)?.clone();
+            let mut app = state.get().write().await;
+            if let Err(e) = app.load(&from).await {
+                warn!("No saved game state: {}", e);
+            }
+
+            let score = app.score(&from.id.to_string()).await;
+            return Ok(Action::ReplyMarkdown(format!(
+                "```\n{}\n```",
+                api::escape_code(&card::render(
+                    &from.first_name,
+                    &score,
+                    app.min_games_for_rate()
+                ))
+            )));
+        }
+
+        "/possible" => {
+            let app = state.get().read().await;
+            if !app.is_playing() {
+                "Start a game with /new first.".into()
+            } else {
+                match app.possible_words().map(|words| words.len()) {
+                    Some(1) => "Only one possible word left.".into(),
+                    Some(count) => format!("{} possible words left.", count),
+                    None => "Words are not validated, so I can't count possible words.".into(),
                 }
             }
         }
-        s.push_str("\n\n");
+
+        "/grid" => {
+            let app = state.get().read().await;
+            match &app.wordle {
+                Some(wordle) if app.is_playing() => {
+                    let game = wordle.game()?;
+                    if game.attempts.is_empty() {
+                        "You haven't made any guesses yet.".into()
+                    } else {
+                        render_share_grid(&game)
+                    }
+                }
+                _ => "You don't have a game in progress. Type /new to start one.".into(),
+            }
+        }
+
+        "/describe" => {
+            let app = state.get().read().await;
+            match &app.wordle {
+                Some(wordle) => describe_game(&wordle.game()?),
+                None => "Start a game with /new first.".into(),
+            }
+        }
+
+        "/freqhint" => {
+            let app = state.get().read().await;
+            match &app.wordle {
+                Some(wordle) if app.is_playing() => letter_hint(&wordle.target_word),
+                _ => "Start a game with /new first.".into(),
+            }
+        }
+
+        "/tone" => {
+            let from = sender(&e)?.clone();
+            let mut app = state.get().write().await;
+            if let Err(e) = app.load(&from).await {
+                warn!("No saved game state: {}", e);
+            }
+
+            match args {
+                "polite" => app.set_polite(true),
+                "rude" => app.set_polite(false),
+                _ => {}
+            }
+
+            if let Err(e) = app.save(&from).await {
+                error!("Error saving game state: {}", e);
+            }
+
+            if app.polite() {
+                "I'll keep it polite. Type /tone rude if you can take it.".into()
+            } else {
+                "I'll be rude. Type /tone polite if it's too much.".into()
+            }
+        }
+
+        "/feedback" => {
+            let app = state.get().read().await;
+            if args.is_empty() {
+                "Usage: /feedback <message>".into()
+            } else if !app.has_admin_chat().await {
+                "Sorry, feedback isn't being collected right now.".into()
+            } else if app
+                .send_feedback(Arc::clone(&e.api), sender(&e)?, args)
+                .await
+            {
+                "Thanks for the feedback!".into()
+            } else {
+                "You just sent feedback. Please wait a minute before sending more.".into()
+            }
+        }
+
+        "/spoilers" => {
+            let from = sender(&e)?.clone();
+            let mut app = state.get().write().await;
+            if let Err(e) = app.load(&from).await {
+                warn!("No saved game state: {}", e);
+            }
+
+            match args {
+                "on" => app.set_spoilers(true),
+                "off" => app.set_spoilers(false),
+                _ => {}
+            }
+
+            if let Err(e) = app.save(&from).await {
+                error!("Error saving game state: {}", e);
+            }
+
+            if app.spoilers() {
+                "Wrong letters are hidden behind spoilers. Type /spoilers off to show them plainly."
+                    .into()
+            } else {
+                "Wrong letters are shown plainly. Type /spoilers on to hide them behind spoilers."
+                    .into()
+            }
+        }
+
+        "/plainletters" => {
+            let from = sender(&e)?.clone();
+            let mut app = state.get().write().await;
+            if let Err(e) = app.load(&from).await {
+                warn!("No saved game state: {}", e);
+            }
+
+            match args {
+                "on" => app.set_plain_letters(true),
+                "off" => app.set_plain_letters(false),
+                _ => {}
+            }
+
+            if let Err(e) = app.save(&from).await {
+                error!("Error saving game state: {}", e);
+            }
+
+            if app.plain_letters() {
+                "Correct letters are shown as plain letters. Type /plainletters off to show them as emoji."
+                    .into()
+            } else {
+                "Correct letters are shown as emoji. Type /plainletters on to show them as plain letters."
+                    .into()
+            }
+        }
+
+        "/terse" => {
+            let from = sender(&e)?.clone();
+            let mut app = state.get().write().await;
+            if let Err(e) = app.load(&from).await {
+                warn!("No saved game state: {}", e);
+            }
+
+            match args {
+                "on" => app.set_terse(true),
+                "off" => app.set_terse(false),
+                _ => {}
+            }
+
+            if let Err(e) = app.save(&from).await {
+                error!("Error saving game state: {}", e);
+            }
+
+            if app.terse() {
+                "Guesses get just the board. Type /terse off to bring back the comments.".into()
+            } else {
+                "Guesses get the board with a comment. Type /terse on to leave out the comments."
+                    .into()
+            }
+        }
+
+        "/assist" => {
+            let from = sender(&e)?.clone();
+            let mut app = state.get().write().await;
+            if let Err(e) = app.load(&from).await {
+                warn!("No saved game state: {}", e);
+            }
+
+            match args {
+                "on" => app.set_assist(true),
+                "off" => app.set_assist(false),
+                _ => {}
+            }
+
+            if let Err(e) = app.save(&from).await {
+                error!("Error saving game state: {}", e);
+            }
+
+            if app.assist() {
+                "A letter is revealed once you've used half your guesses. Type /assist off to stop."
+                    .into()
+            } else {
+                "No letters are revealed. Type /assist on to get one once you've used half your guesses."
+                    .into()
+            }
+        }
+
+        "/settings" => handle_settings(&e, &state).await?,
+
+        "/analytics" => handle_analytics(&e, &state, args).await?,
+
+        "/reminders" => handle_reminders(&e, &state, args).await?,
+
+        "/verify" => {
+            // Greens can only be expected to stay put if the bot enforces hard mode.
+            let hard_mode = state.get().read().await.hard_mode() != wordle::HardMode::Off;
+            let verified = grid::parse(args)
+                .and_then(|rows| grid::verify(&rows, hard_mode).map(|_| rows.len()));
+            match verified {
+                Ok(rows) => format!("That grid is consistent, with {} rows.", rows),
+                Err(grid::GridError::Empty) => {
+                    "Paste a shared grid after /verify to check it.".into()
+                }
+                Err(e) => format!("That grid isn't consistent: {}.", e),
+            }
+        }
+
+        _ => "I don't know that command.".into(),
+    };
+
+    Ok(Action::ReplyText(reply))
+}
+
+/// handle_recent lists the outcomes of the user's last few games, newest first.
+async fn handle_recent(e: &Event, state: &State<App>) -> Result<String, anyhow::Error> {
+    let from = sender(e)?.clone();
+    let mut app = state.get().write().await;
+    if let Err(e) = app.load(&from).await {
+        warn!("No saved game state: {}", e);
+    }
+
+    let recent = app.recent_games();
+    if recent.is_empty() {
+        return Ok("You haven't finished any games yet.".into());
+    }
+
+    let chat_id = e.update.chat_id()?;
+    let mut reply = format!("Your last {} games, newest first:", recent.len());
+    for result in recent.iter().rev() {
+        reply.push_str(&if result.won {
+            format!(
+                "\n{}: won in {}/{}",
+                result.target, result.guesses, result.max_attempts
+            )
+        } else if app.is_held_back(chat_id, &result.target).await {
+            "\n(hidden until the puzzle closes): lost".into()
+        } else {
+            format!("\n{}: lost", result.target)
+        });
+    }
+
+    Ok(reply)
+}
+
+/// handle_replay re-renders the sender's most recently finished game, so it can be
+/// shared again after a new game has started.
+async fn handle_replay(e: &Event, state: &State<App>) -> Result<Action, anyhow::Error> {
+    let from = sender(e)?.clone();
+    let mut app = state.get().write().await;
+    if let Err(e) = app.load(&from).await {
+        warn!("No saved game state: {}", e);
+    }
+
+    let Some(wordle) = app.last_finished() else {
+        return Ok(Action::ReplyText(
+            "You haven't finished any games yet.".into(),
+        ));
+    };
+
+    let game = wordle.game()?;
+    let result = match game.state {
+        wordle::State::Won => format!(
+            "You guessed {} in {}/{}.",
+            wordle.target_word,
+            game.attempts.len(),
+            game.max_attempts
+        ),
+        _ if app
+            .is_held_back(e.update.chat_id()?, &wordle.target_word)
+            .await =>
+        {
+            "The word will be revealed when the puzzle closes.".into()
+        }
+        _ => format!("The word was {}.", wordle.target_word),
+    };
+
+    Ok(Action::ReplyMarkdown(format!(
+        "{}\n{}\n\n{}",
+        render_game(&game, app.spoilers(), app.plain_letters()),
+        escape_md(&result),
+        render_share_grid(&game)
+    )))
+}
+
+/// handle_test_word starts a practice game for an admin with the given target word,
+/// to try out its board. Practice games don't count toward scores.
+async fn handle_test_word(
+    e: &Event,
+    state: &State<App>,
+    args: &str,
+) -> Result<String, anyhow::Error> {
+    let from = sender(e)?.clone();
+    let mut app = state.get().write().await;
+    if !app.is_admin(&from) {
+        return Ok("You are not an admin.".into());
+    }
+
+    if args.is_empty() {
+        return Ok("Usage: /testword WORD".into());
+    }
+
+    if let Err(e) = app.load(&from).await {
+        warn!("No saved game state: {}", e);
+    }
+    let target_word = match app.start_practice(args) {
+        Ok(target_word) => target_word,
+        Err(e) => return Ok(format!("Sorry, {}.", e)),
+    };
+
+    // Save the practice game like /new does, so the next guess is played against it
+    // rather than the saved game. It isn't counted toward the score.
+    if let Err(e) = app.save(&from).await {
+        error!("Error saving game state: {}", e);
+    }
+    app.track_game(e.update.chat_id()?, &from).await;
+
+    Ok(format!(
+        "Practice game with {} started. It won't count toward your score. Send your guess.",
+        target_word
+    ))
+}
+
+/// handle_solve has the bot's solver play the given word, and shows its guesses.
+async fn handle_solve(e: &Event, state: &State<App>, args: &str) -> Result<String, anyhow::Error> {
+    let app = state.get().read().await;
+    if !app.is_admin(sender(e)?) {
+        return Ok("You are not an admin.".into());
+    }
+
+    if args.is_empty() {
+        return Ok("Usage: /solve WORD".into());
+    }
+
+    let word = args.to_uppercase();
+    if !app.is_valid_word(args.to_string()) {
+        return Ok(format!("{} isn't a valid word.", word));
+    }
+
+    Ok(match app.solve(&word) {
+        Ok(Some(solution)) if solution.won => format!(
+            "Solved {} in {}/{}: {}",
+            word,
+            solution.guesses.len(),
+            wordle::MAX_ATTEMPTS,
+            solution.guesses.join(", ")
+        ),
+        Ok(Some(solution)) => format!(
+            "Couldn't solve {} in {} guesses: {}",
+            word,
+            wordle::MAX_ATTEMPTS,
+            solution.guesses.join(", ")
+        ),
+        Ok(None) => "Words aren't validated, so there's nothing to solve with.".into(),
+        Err(e) => format!("Sorry, {}.", e),
+    })
+}
+
+/// handle_give_up ends the sender's game as a loss, and shows the board and the
+/// target word, unless words are hidden on loss.
+async fn handle_give_up(e: &Event, state: &State<App>) -> Result<Action, anyhow::Error> {
+    let from = sender(e)?.clone();
+    let mut app = state.get().write().await;
+    if let Err(e) = app.load(&from).await {
+        warn!("No saved game state: {}", e);
+    }
+
+    if !app.is_playing() {
+        return Ok(Action::ReplyText(
+            "You don't have a game in progress. Type /new to start one.".into(),
+        ));
+    }
+
+    let turn = app.give_up(&from).await?;
+    app.track_game(e.update.chat_id()?, &from).await;
+
+    let target_word = turn.target.to_uppercase();
+    let reply = format!(
+        "{}{}",
+        render_game(&turn.board, app.spoilers(), app.plain_letters()),
+        escape_md(&format!(
+            "\nYou gave up! {} {}\nYour score: {}",
+            revealed_target(e, &app, &target_word).await?,
+            app.lose_emoji(),
+            format_score(&turn.score, app.min_games_for_rate())
+        ))
+    );
+
+    app.admin_log(
+        Arc::clone(&e.api),
+        format!(
+            "{} ({}) gave up on '{}'.",
+            from.first_name,
+            from.username.clone().unwrap_or_default(),
+            logged_target(&app, &target_word),
+        ),
+    )
+    .await;
+
+    Ok(Action::ReplyMarkdown(reply))
+}
+
+/// handle_analytics shows or sets whether the sender's events are forwarded to the
+/// admin log.
+async fn handle_analytics(
+    e: &Event,
+    state: &State<App>,
+    args: &str,
+) -> Result<String, anyhow::Error> {
+    let from = sender(e)?.clone();
+    let mut app = state.get().write().await;
+    if let Err(e) = app.load(&from).await {
+        warn!("No saved game state: {}", e);
+    }
+
+    match args {
+        "" => {}
+        "on" => app.set_analytics(Some(true)),
+        "off" => app.set_analytics(Some(false)),
+        _ => return Ok("Usage: /analytics on|off".into()),
+    }
+
+    if let Err(e) = app.save(&from).await {
+        error!("Error saving game state: {}", e);
+    }
+
+    Ok(if app.analytics() {
+        "Analytics are on. Your games are shared with the bot admin. Type /analytics off to stop."
+    } else {
+        "Analytics are off. Your games are not shared with the bot admin."
+    }
+    .into())
+}
+
+/// handle_settings shows all of the sender's settings, with the command that
+/// changes each one.
+async fn handle_settings(e: &Event, state: &State<App>) -> Result<String, anyhow::Error> {
+    let from = sender(e)?.clone();
+    let mut app = state.get().write().await;
+    if let Err(e) = app.load(&from).await {
+        warn!("No saved game state: {}", e);
     }
-    s
+
+    let on_off = |on: bool| if on { "on" } else { "off" };
+    let mut reply = format!(
+        "Your settings:\nWord length: {} (/length)\nGuesses: {} (/tries)\nTone: {} (/tone)\nSpoilers: {} (/spoilers)\nPlain letters: {} (/plainletters)\nTerse: {} (/terse)\nAssist: {} (/assist)\nAnalytics: {} (/analytics)",
+        app.settings()
+            .word_length
+            .map_or("any".to_string(), |len| len.to_string()),
+        app.max_attempts(),
+        if app.polite() { "polite" } else { "rude" },
+        on_off(app.spoilers()),
+        on_off(app.plain_letters()),
+        on_off(app.terse()),
+        on_off(app.assist()),
+        on_off(app.analytics()),
+    );
+    if app.reminder_after().is_some() {
+        reply.push_str(&format!(
+            "\nReminders: {} (/reminders)",
+            on_off(app.reminders())
+        ));
+    }
+
+    Ok(reply)
 }
 
-pub async fn handle_new_game(e: Event, state: State<App>) -> Result<Action, anyhow::Error> {
-    // Get the sender's first name
-    let from = e.update.get_message()?.clone().from.unwrap_or_default();
+/// handle_reminders shows or sets whether the sender is reminded about games they
+/// left unfinished.
+async fn handle_reminders(
+    e: &Event,
+    state: &State<App>,
+    args: &str,
+) -> Result<String, anyhow::Error> {
+    let from = sender(e)?.clone();
+    let mut app = state.get().write().await;
+    let Some(reminder_after) = app.reminder_after() else {
+        return Ok("Reminders are not enabled.".into());
+    };
 
-    // Get the application state
+    if let Err(e) = app.load(&from).await {
+        warn!("No saved game state: {}", e);
+    }
+
+    match args {
+        "" => {}
+        "on" => app.set_reminders(true),
+        "off" => app.set_reminders(false),
+        _ => return Ok("Usage: /reminders on|off".into()),
+    }
+
+    if let Err(e) = app.save(&from).await {
+        error!("Error saving game state: {}", e);
+    }
+    app.track_game(e.update.chat_id()?, &from).await;
+
+    Ok(if app.reminders() {
+        format!(
+            "Reminders are on. You'll get a nudge if you leave a game alone for {} hours. Type /reminders off to stop.",
+            reminder_after.as_secs() / 3600
+        )
+    } else {
+        "Reminders are off. Type /reminders on to get a nudge about unfinished games.".into()
+    })
+}
+
+/// handle_length shows or sets the sender's preferred target word length.
+async fn handle_length(e: &Event, state: &State<App>, args: &str) -> Result<String, anyhow::Error> {
+    let from = sender(e)?.clone();
     let mut app = state.get().write().await;
-    let target_word;
     if let Err(e) = app.load(&from).await {
         warn!("No saved game state: {}", e);
     }
 
-    target_word = app.start_game().await?;
-    app.inc_games(&from).await; // saves state
+    let lengths = app
+        .word_lengths()
+        .iter()
+        .map(|l| l.to_string())
+        .collect::<Vec<_>>()
+        .join(", ");
 
-    info!(
-        "Starting new game with {} ({}), target word: {}.",
-        from.first_name,
-        from.username.clone().unwrap_or("unknown".into()),
-        target_word
-    );
+    let word_length = match args {
+        "" => {
+            return Ok(format!(
+                "Available word lengths: {}. Type /length N to pick one, or /length any.",
+                lengths
+            ))
+        }
+        "any" => None,
+        _ => match args.parse::<usize>() {
+            Ok(len) => Some(len),
+            Err(_) => return Ok("Usage: /length N".into()),
+        },
+    };
+
+    if let Err(e) = app.set_word_length(word_length) {
+        return Ok(format!(
+            "Sorry, {}. Available word lengths: {}.",
+            e, lengths
+        ));
+    }
+
+    if let Err(e) = app.save(&from).await {
+        error!("Error saving game state: {}", e);
+    }
+
+    Ok(match word_length {
+        Some(len) => format!("Your next game will use a {}-letter word.", len),
+        None => "Your next game can use a word of any length.".into(),
+    })
+}
+
+/// handle_tries shows or sets the number of guesses the sender gets per game.
+async fn handle_tries(e: &Event, state: &State<App>, args: &str) -> Result<String, anyhow::Error> {
+    let from = sender(e)?.clone();
+    let mut app = state.get().write().await;
+    if let Err(e) = app.load(&from).await {
+        warn!("No saved game state: {}", e);
+    }
+
+    let max_attempts = match args {
+        "" => {
+            return Ok(format!(
+                "You get {} guesses per game. Type /tries N to pick between {} and {}, or /tries default.",
+                app.max_attempts(),
+                MAX_ATTEMPTS_RANGE.start(),
+                MAX_ATTEMPTS_RANGE.end()
+            ))
+        }
+        "default" => None,
+        _ => match args.parse::<usize>() {
+            Ok(n) => Some(n),
+            Err(_) => return Ok("Usage: /tries N".into()),
+        },
+    };
+
+    if let Err(e) = app.set_max_attempts(max_attempts) {
+        return Ok(format!("Sorry, {}.", e));
+    }
+
+    if let Err(e) = app.save(&from).await {
+        error!("Error saving game state: {}", e);
+    }
+
+    Ok(format!(
+        "Your next game will give you {} guesses.",
+        app.max_attempts()
+    ))
+}
+
+/// handle_peek shows admins the target word of another chat's game.
+async fn handle_peek(e: &Event, state: &State<App>, args: &str) -> Result<String, anyhow::Error> {
+    let app = state.get().read().await;
+    if !app.is_admin(sender(e)?) {
+        return Ok("You are not an admin.".into());
+    }
+
+    let chat_id = match parse_chat_id(args) {
+        Ok(chat_id) => chat_id,
+        Err(e) => return Ok(format!("Sorry, {}. Usage: /peek <chat_id>", e)),
+    };
+
+    Ok(match app.tracked_game(chat_id).await {
+        Some(wordle) => format!(
+            "Chat {} is playing {} ({} attempts so far).",
+            chat_id,
+            wordle.target_word,
+            wordle.attempts.len()
+        ),
+        None => format!("No game in progress for chat {}.", chat_id),
+    })
+}
+
+/// handle_setscore lets admins overwrite a user's all-time score.
+async fn handle_setscore(
+    e: &Event,
+    state: &State<App>,
+    args: &str,
+) -> Result<String, anyhow::Error> {
+    let app = state.get().read().await;
+    let admin = sender(e)?;
+    if !app.is_admin(admin) {
+        return Ok("You are not an admin.".into());
+    }
+
+    let usage = "Usage: /setscore <user_id> <wins> <games>";
+    let [user_id, wins, games] = args.split_whitespace().collect::<Vec<_>>()[..] else {
+        return Ok(usage.into());
+    };
+    let (Ok(wins), Ok(games)) = (wins.parse::<u32>(), games.parse::<u32>()) else {
+        return Ok(usage.into());
+    };
+    if let Err(e) = parse_chat_id(user_id) {
+        return Ok(format!("Sorry, {}. {}", e, usage));
+    }
+
+    let score = match app.set_score(user_id, wins, games).await {
+        Ok(score) => score,
+        Err(e) => return Ok(format!("Could not set score of user {}: {}", user_id, e)),
+    };
 
     app.admin_log(
         Arc::clone(&e.api),
         format!(
-            "{} ({}) starting a new game with word {}.",
-            from.first_name,
-            from.username.clone().unwrap_or_default(),
-            target_word,
+            "{} set the score of user {} to {}/{}.",
+            admin.username.clone().unwrap_or_default(),
+            user_id,
+            score.wins,
+            score.games
         ),
     )
     .await;
 
-    let first_game = if app.score(&from.id.to_string()).await.games == 0 {
-        "This is your first game.".to_string()
-    } else {
-        format!("Your score: {}.", app.score(&from.id.to_string()).await)
-    };
+    Ok(format!("Score of user {} set to {}.", user_id, score))
+}
 
-    return Ok(Action::ReplyText(format!(
-        "Hi {}, Welcome to {}!\n\n{}\nGuess the {}-letter word.",
-        from.first_name,
+/// handle_reload lets admins re-read the word lists without restarting the bot. Games
+/// in progress are unaffected.
+async fn handle_reload(e: &Event, state: &State<App>) -> Result<String, anyhow::Error> {
+    let app = state.get().read().await;
+    if !app.is_admin(sender(e)?) {
+        return Ok("You are not an admin.".into());
+    }
+
+    Ok(match app.reload_words().await {
+        Ok((targets, valid)) => format!(
+            "Reloaded {} target words and {} valid words.",
+            targets, valid
+        ),
+        Err(e) => format!("Could not reload word lists: {}", e),
+    })
+}
+
+/// format_uptime renders a duration as days, hours, minutes and seconds, e.g. "2d 3h 0m 5s".
+pub fn format_uptime(uptime: Duration) -> String {
+    let secs = uptime.as_secs();
+    format!(
+        "{}d {}h {}m {}s",
+        secs / 86400,
+        secs / 3600 % 24,
+        secs / 60 % 60,
+        secs % 60
+    )
+}
+
+/// handle_status shows admins the bot's version, uptime and load.
+async fn handle_status(e: &Event, state: &State<App>) -> Result<String, anyhow::Error> {
+    let app = state.get().read().await;
+    if !app.is_admin(sender(e)?) {
+        return Ok("You are not an admin.".into());
+    }
+
+    Ok(format!(
+        "{} v{}\nUptime: {}\nTarget words: {}\nValid words: {}\nActive games: {}",
         app.game_name,
-        first_game,
-        target_word.len()
-    )));
+        env!("CARGO_PKG_VERSION"),
+        format_uptime(app.uptime()),
+        app.target_words().len(),
+        app.valid_word_count(),
+        app.active_games().await
+    ))
 }
 
-pub async fn handle_bot_command(e: Event, state: State<App>) -> Result<Action, anyhow::Error> {
-    // Get the command
-    let command = e
-        .update
-        .get_message()?
-        .text
-        .as_ref()
-        .ok_or(anyhow!("No command"))?;
-
-    let reply = match command.as_str() {
-        "/help" => {
-            let game_name = state.get().read().await.game_name.clone();
-            format!(
-                "Welcome to {}! The goal of the game is to guess the target word within 6 tries.
+/// The number of words per /listwords page.
+const WORDS_PER_PAGE: usize = 50;
 
-Type /new to restart the game or /score to see your score",
-                game_name
-            )
-        }
+/// handle_listwords shows admins a page of the target word list, in alphabetical
+/// order.
+async fn handle_listwords(
+    e: &Event,
+    state: &State<App>,
+    args: &str,
+) -> Result<String, anyhow::Error> {
+    let app = state.get().read().await;
+    if !app.is_admin(sender(e)?) {
+        return Ok("You are not an admin.".into());
+    }
 
-        "/new" => {
-            return handle_new_game(e, state).await;
-        }
+    let page = match args {
+        "" => 1,
+        _ => match args.parse::<usize>() {
+            Ok(page) if page > 0 => page,
+            _ => return Ok("Usage: /listwords [page]".into()),
+        },
+    };
 
-        "/start" => {
-            return handle_new_game(e, state).await;
-        }
+    let mut words = app.target_words();
+    words.sort();
+    let pages = words.len().div_ceil(WORDS_PER_PAGE);
+    let chunk = words
+        .chunks(WORDS_PER_PAGE)
+        .nth(page - 1)
+        .map(|chunk| chunk.join(", "));
 
-        "/admin" => {
-            let mut app = state.get().write().await;
-            if app
-                .auth_admin(
-                    e.update
-                        .from_user()?
-                        .username
-                        .clone()
-                        .unwrap_or_default()
-                        .as_str(),
-                    e.update.chat_id()?,
+    let Some(chunk) = chunk else {
+        return Ok(format!(
+            "Page {} is empty. There are {} pages.",
+            page, pages
+        ));
+    };
+
+    let mut reply = format!(
+        "Target words, page {} of {} ({} words):\n\n{}",
+        page,
+        pages,
+        words.len(),
+        chunk
+    );
+    if page < pages {
+        reply.push_str(&format!(
+            "\n\nType /listwords {} for the next page.",
+            page + 1
+        ));
+    }
+    Ok(reply)
+}
+
+/// The number of players shown on the leaderboard.
+const LEADERBOARD_SIZE: usize = 10;
+
+/// handle_leaderboard shows the players with the most wins.
+async fn handle_leaderboard(state: &State<App>) -> Result<String, anyhow::Error> {
+    let app = state.get().read().await;
+    let entries = app.leaderboard().await?;
+    if entries.is_empty() {
+        return Ok("No scores yet.".into());
+    }
+
+    let mut reply = String::from("Leaderboard:");
+    for (i, entry) in entries.iter().take(LEADERBOARD_SIZE).enumerate() {
+        reply.push_str(&format!(
+            "\n{}. {} {}",
+            i + 1,
+            entry.name,
+            format_score(&entry.score, app.min_games_for_rate())
+        ));
+    }
+
+    Ok(reply)
+}
+
+/// handle_rank tells the player where they stand by win rate among every player with
+/// enough games.
+async fn handle_rank(e: &Event, state: &State<App>) -> Result<String, anyhow::Error> {
+    let from = sender(e)?.clone();
+    let mut app = state.get().write().await;
+    if let Err(e) = app.load(&from).await {
+        warn!("No saved game state: {}", e);
+    }
+
+    let user_id = from.id.to_string();
+    let min_games = app.min_games_for_rate().max(1);
+    let entries = app.leaderboard().await?;
+    Ok(
+        match leaderboard::win_rate_rank(&entries, &user_id, min_games) {
+            Some((place, players)) => {
+                format!("You're #{} of {} by win rate.", place, players)
+            }
+            // The leaderboard is cached, so recent players may be missing.
+            None if app.score(&user_id).await.games >= min_games => {
+                "You're not on the leaderboard yet. Try again in a minute.".into()
+            }
+            None => {
+                let needed = min_games - app.score(&user_id).await.games;
+                format!(
+                    "You need {} more game{} to be ranked.",
+                    needed,
+                    if needed == 1 { "" } else { "s" }
                 )
-                .await?
-            {
-                "Admin messages routed to this chat.".into()
-            } else {
-                "You are not an admin.".into()
             }
+        },
+    )
+}
+
+/// handle_word_stats shows admins how a target word has fared.
+async fn handle_word_stats(
+    e: &Event,
+    state: &State<App>,
+    args: &str,
+) -> Result<String, anyhow::Error> {
+    let app = state.get().read().await;
+    if !app.is_admin(sender(e)?) {
+        return Ok("You are not an admin.".into());
+    }
+
+    if args.is_empty() {
+        return Ok("Usage: /wordstats WORD".into());
+    }
+
+    let word = args.to_uppercase();
+    let Some(stats) = app.word_stats(&word).await else {
+        return Ok(format!("{} hasn't been served yet.", word));
+    };
+
+    Ok(format!(
+        "{}: served {}, won {}, lost {}. Average winning guesses: {}.",
+        word,
+        stats.serves,
+        stats.wins,
+        stats.losses,
+        stats
+            .average_winning_guesses()
+            .map(|avg| format!("{:.1}", avg))
+            .unwrap_or("-".into())
+    ))
+}
+
+/// The longest message Telegram accepts is 4096 characters. CSV exports are split
+/// into messages of at most this many, leaving room for the code block markup.
+const MAX_EXPORT_MESSAGE_LEN: usize = 4000;
+
+/// handle_export_csv sends admins every player's all-time score as CSV. mobot can't
+/// upload documents, so the CSV is sent as code blocks, split at line breaks to fit
+/// in messages.
+async fn handle_export_csv(e: &Event, state: &State<App>) -> Result<Action, anyhow::Error> {
+    let app = state.get().read().await;
+    if !app.is_admin(sender(e)?) {
+        return Ok(Action::ReplyText("You are not an admin.".into()));
+    }
+
+    let csv = leaderboard::to_csv(&app.leaderboard().await?);
+    let mut chunks = vec![String::new()];
+    for line in csv.lines() {
+        let chunk = chunks.last_mut().unwrap();
+        if !chunk.is_empty() && chunk.len() + line.len() + 1 > MAX_EXPORT_MESSAGE_LEN {
+            chunks.push(String::new());
         }
+        let chunk = chunks.last_mut().unwrap();
+        chunk.push_str(line);
+        chunk.push('\n');
+    }
 
-        "/score" => {
-            let from = e.update.get_message()?.clone().from.unwrap_or_default();
-            let mut app = state.get().write().await;
+    for chunk in chunks {
+        e.api
+            .send_message(&api::SendMessageRequest {
+                chat_id: e.update.chat_id()?,
+                text: format!("```\n{}```", api::escape_code(&chunk)),
+                parse_mode: Some(api::ParseMode::MarkdownV2),
+                ..Default::default()
+            })
+            .await?;
+    }
 
-            // Get the application state
-            if let Err(e) = app.load(&from).await {
-                warn!("No saved game state: {}", e);
-                format!("You have not played any games yet.")
-            } else {
-                format!("Your score: {}", app.score(&from.id.to_string()).await)
-            }
+    Ok(Action::Done)
+}
+
+/// handle_season shows admins the current season and the archived ones.
+async fn handle_season(e: &Event, state: &State<App>) -> Result<String, anyhow::Error> {
+    let app = state.get().read().await;
+    if !app.is_admin(sender(e)?) {
+        return Ok("You are not an admin.".into());
+    }
+
+    if !app.has_seasons() {
+        return Ok("Seasons are not enabled.".into());
+    }
+
+    let season = app.season().await;
+    let days = season::now().saturating_sub(season.started_at) / (24 * 60 * 60);
+    let archives = app
+        .archived_seasons()
+        .await?
+        .iter()
+        .map(|n| n.to_string())
+        .collect::<Vec<_>>();
+
+    Ok(format!(
+        "Season {} started {} days ago.\nArchived seasons: {}",
+        season.number,
+        days,
+        if archives.is_empty() {
+            "none".into()
+        } else {
+            archives.join(", ")
         }
+    ))
+}
 
-        _ => "I don't know that command.".into(),
-    };
+/// handle_edited_message replies to edited messages. Edits don't count as guesses,
+/// since the original message was already played.
+pub async fn handle_edited_message(e: Event, state: State<App>) -> Result<Action, anyhow::Error> {
+    log_update(&e, &state).await;
+    reply_to_user_errors(edited_message(&e))
+}
 
-    Ok(Action::ReplyText(reply))
+fn edited_message(e: &Event) -> Result<Action, anyhow::Error> {
+    let from = sender(e)?;
+    info!(
+        "{} ({}) edited a message",
+        from.first_name,
+        from.username.clone().unwrap_or("unknown".into())
+    );
+
+    Ok(Action::ReplyText(
+        "Edits don't count. Send your guess as a new message.".into(),
+    ))
 }
 
 /// handle_chat_event is the main Telegram handler for the bot.
 pub async fn handle_chat_event(e: Event, state: State<App>) -> Result<Action, anyhow::Error> {
-    // Get the message
-    let message = e.update.get_message()?.clone().text.unwrap().clone();
+    log_update(&e, &state).await;
+    restore_game(&e, &state).await;
+    let action = reply_to_user_errors(chat_event(e, state.clone()).await);
+    release_game(&state).await;
+    action
+}
+
+async fn chat_event(e: Event, state: State<App>) -> Result<Action, anyhow::Error> {
+    // Get the message, without any quotes wrapped around the guess.
+    let message = message_text(e.update.get_message()?)?;
+    let message = strip_quotes(&message).to_string();
 
     // Get the sender's first name
-    let from = e.update.get_message()?.clone().from.unwrap_or_default();
+    let from = sender(&e)?.clone();
 
-    // Get the application state
+    // Some clients send a message twice. Ignore the repeat, so a guess isn't played
+    // twice.
+    let chat_id = e.update.chat_id()?;
+    if state
+        .get()
+        .read()
+        .await
+        .is_duplicate(chat_id, &message, Instant::now())
+        .await
     {
+        info!(
+            "{} ({}) sent {} twice, ignoring the repeat",
+            from.first_name,
+            from.username.clone().unwrap_or("unknown".into()),
+            message
+        );
+        return Ok(Action::Done);
+    }
+
+    // Get the application state
+    let after_score = {
         let mut state = state.get().write().await;
+        let after_score = state.take_after_score();
         if let Err(err) = state.load(&from).await {
             warn!("No saved game state: {}", err);
             state
@@ -180,15 +1689,77 @@ pub async fn handle_chat_event(e: Event, state: State<App>) -> Result<Action, an
                 )
                 .await;
         }
+        after_score
+    };
+
+    // Right after a win, players often send a celebratory message. Don't treat it as
+    // the start of a new game.
+    {
+        let app = state.get().read().await;
+        if !app.is_playing() && app.in_post_win_cooldown() {
+            return Ok(Action::ReplyText(
+                "Nice game! Say /new to play again.".into(),
+            ));
+        }
     }
 
-    // If there's no active game, start one.
-    if !state.get().read().await.is_playing() {
+    // If there's no active game, start one. Otherwise, note which game the guess is
+    // for, as a /new may start another before the guess is played.
+    let playing_serial = {
+        let app = state.get().read().await;
+        app.is_playing().then(|| app.game_serial())
+    };
+    let Some(game_serial) = playing_serial else {
         // Scan the list for an unplayed word, or pick a random one.
-        return handle_new_game(e, state).await;
-    }
+        let welcome = new_game(&e, &state, None).await?;
+
+        // Unless configured otherwise, the first message only starts the game.
+        let (first_message_counts, game_serial) = {
+            let app = state.get().read().await;
+            (
+                app.is_playing()
+                    && app.first_message_counts()
+                    && app.wordle.as_ref().unwrap().target_word.len() == message.len(),
+                app.game_serial(),
+            )
+        };
+
+        if !first_message_counts {
+            // Players who check their score after a game may think the game is still
+            // on, and send a guess that only starts a new one.
+            if after_score && state.get().read().await.is_playing() {
+                return Ok(Action::ReplyText(format!(
+                    "{}\n\nYour last game was over, so that started a new one. Send your guess now.",
+                    welcome
+                )));
+            }
+            return Ok(Action::ReplyText(welcome));
+        }
+
+        let reply = play_turn(&e, &state, &from, &message, game_serial).await?;
+        return Ok(Action::ReplyMarkdown(format!(
+            "{}\n\n{}",
+            escape_md(welcome.as_str()),
+            reply
+        )));
+    };
 
     // There's an active game, so play a turn.
+    Ok(Action::ReplyMarkdown(
+        play_turn(&e, &state, &from, &message, game_serial).await?,
+    ))
+}
+
+/// play_turn plays the message as a guess in the game identified by `game_serial`
+/// and returns the Markdown reply. If another game has started since, the player
+/// is asked to guess again.
+async fn play_turn(
+    e: &Event,
+    state: &State<App>,
+    from: &User,
+    message: &str,
+    game_serial: u64,
+) -> Result<String, anyhow::Error> {
     info!(
         "{} ({}) guessed {}",
         from.first_name,
@@ -196,32 +1767,40 @@ pub async fn handle_chat_event(e: Event, state: State<App>) -> Result<Action, an
         message
     );
 
-    // Play a turn
-    let turn = state
-        .get()
-        .write()
-        .await
-        .play_turn(&from, message.clone())
+    // Play a turn, and build the reply under the same lock.
+    let mut app = state.get().write().await;
+    let turn = app
+        .play_turn_in(game_serial, from, message.to_string())
         .await?;
+    app.track_game(e.update.chat_id()?, from).await;
 
-    let (mut reply, target_word, attempted_letters, score) = {
-        let app = state.get().read().await;
-        let wordle = app.wordle.as_ref().unwrap();
-        let reply = render_game(&wordle.game()?);
-        let target_word = wordle.target_word.clone().to_uppercase();
-        let attempted_letters = wordle
-            .game()?
-            .attempted_letters()
-            .iter()
-            .map(|c| format!("`{}`", c))
-            .collect::<Vec<_>>()
-            .join(" ");
-        let score = app.score(&from.id.to_string()).await;
-
-        (reply, target_word, attempted_letters, score)
+    let mut reply = if app.terse() {
+        render_board(&turn.board, app.spoilers(), app.plain_letters())
+    } else {
+        render_game(&turn.board, app.spoilers(), app.plain_letters())
+    };
+    let target_word = turn.target.to_uppercase();
+    let attempted_letters = turn
+        .board
+        .attempted_letters_in_order()
+        .iter()
+        .map(|c| format!("`{}`", c))
+        .collect::<Vec<_>>()
+        .join(" ");
+    let comment = try_again_comment(
+        turn.board
+            .max_attempts
+            .saturating_sub(turn.board.attempts.len()),
+        app.polite(),
+    );
+    let comment = if app.no_emoji() {
+        without_emoji(comment)
+    } else {
+        comment.to_string()
     };
+    let score = format_score(&turn.score, app.min_games_for_rate());
 
-    match turn {
+    match turn.move_ {
         Move::InvalidWord => {
             reply = format!(
                 "Sorry {}, that's not a valid word\\. Try again\\.",
@@ -232,19 +1811,56 @@ pub async fn handle_chat_event(e: Event, state: State<App>) -> Result<Action, an
             reply = format!(
                 "Sorry {}, the word must be {} letters long\\. Try again\\.",
                 escape_md(from.first_name.as_str()),
-                target_word.len()
+                target_word.chars().count()
             )
         }
-        Move::Valid => reply.push_str(
-            format!(
-                "\nNice try\\. Guess another word\\?\nAttempts: {}",
-                attempted_letters
+        Move::NotLetters => {
+            reply = format!(
+                "Sorry {}, letters only, please\\.",
+                escape_md(from.first_name.as_str())
+            )
+        }
+        Move::RepeatedLetters => {
+            reply = format!(
+                "Sorry {}, no letter may appear twice in a guess\\. Try again\\.",
+                escape_md(from.first_name.as_str())
+            )
+        }
+        Move::BrokeHardMode(ref reason) => {
+            reply = format!(
+                "Sorry {}, this is hard mode: {}\\. Try again\\.",
+                escape_md(from.first_name.as_str()),
+                escape_md(reason)
+            )
+        }
+        Move::GameChanged => {
+            reply = format!(
+                "Sorry {}, a new game started before your guess arrived\\. Send it again\\.",
+                escape_md(from.first_name.as_str())
             )
-            .as_str(),
+        }
+        Move::Valid if app.terse() => {
+            reply.push_str(format!("Attempts: {}", attempted_letters).as_str())
+        }
+        Move::Valid => reply.push_str(
+            format!("\n{}\nAttempts: {}", escape_md(&comment), attempted_letters).as_str(),
         ),
         Move::Won => {
             reply.push_str(
-                escape_md(format!("\nYou won! \u{1F46F}\nYour score: {}", score).as_str()).as_str(),
+                escape_md(
+                    format!(
+                        "\nYou won! {} {}\nYour score: {}",
+                        app.win_emoji(),
+                        celebration_for(
+                            turn.board.attempts.len(),
+                            turn.board.max_attempts,
+                            app.polite()
+                        ),
+                        score
+                    )
+                    .as_str(),
+                )
+                .as_str(),
             );
             info!(
                 "{} ({}) won with {}",
@@ -257,8 +1873,10 @@ pub async fn handle_chat_event(e: Event, state: State<App>) -> Result<Action, an
             reply.push_str(
                 escape_md(
                     format!(
-                        "\nYou lost! Target word: {} \u{1F979}\nYour score: {}",
-                        target_word, score
+                        "\nYou lost! {} {}\nYour score: {}",
+                        revealed_target(e, &app, &target_word).await?,
+                        app.lose_emoji(),
+                        score
                     )
                     .as_str(),
                 )
@@ -274,28 +1892,55 @@ pub async fn handle_chat_event(e: Event, state: State<App>) -> Result<Action, an
         }
     }
 
-    state
-        .get()
-        .read()
-        .await
-        .admin_log(
-            Arc::clone(&e.api),
-            format!(
-                "{} ({}) played word '{}' against '{}' {}.",
-                from.first_name,
-                from.username.clone().unwrap_or_default(),
-                message,
-                target_word,
-                match turn {
-                    Move::InvalidWord => "which was invalid",
-                    Move::InvalidLength => "which was the wrong length",
-                    Move::Valid => "which was valid",
-                    Move::Won => "and won",
-                    Move::Lost => "and lost",
-                }
-            ),
-        )
-        .await;
+    // In group chats, a win also shows how the winner compares to the group.
+    if is_group_chat(e) && matches!(turn.move_, Move::Won | Move::Lost) {
+        let chat_id = e.update.chat_id()?;
+        app.add_chat_player(chat_id, from).await;
+        if matches!(turn.move_, Move::Won) {
+            let (average, players) = app.chat_score(chat_id).await;
+            reply.push_str(&escape_md(&format!(
+                "\n{}",
+                format_scoreboard(&from.first_name, &turn.score, average, players)
+            )));
+        }
+    }
 
-    Ok(Action::ReplyMarkdown(reply))
+    // The assist reveals a letter after the rest of the reply.
+    if let Some((position, letter)) = turn.reveal {
+        reply.push_str(&escape_md(&format!(
+            "\nYou're halfway there, so here's a letter: number {} is {}.",
+            position, letter
+        )));
+    }
+
+    if app.quiet_admin() && !matches!(turn.move_, Move::Won | Move::Lost) {
+        return Ok(reply);
+    }
+
+    // Build the log message under the lock, but send it after releasing it.
+    let log = app.admin_log(
+        Arc::clone(&e.api),
+        format!(
+            "{} ({}) played word '{}' against '{}' {}.",
+            from.first_name,
+            from.username.clone().unwrap_or_default(),
+            message,
+            logged_target(&app, &target_word),
+            match turn.move_ {
+                Move::InvalidWord => "which was invalid",
+                Move::InvalidLength => "which was the wrong length",
+                Move::NotLetters => "which wasn't letters",
+                Move::RepeatedLetters => "which repeated a letter",
+                Move::BrokeHardMode(_) => "which broke hard mode",
+                Move::GameChanged => "which was meant for an earlier game",
+                Move::Valid => "which was valid",
+                Move::Won => "and won",
+                Move::Lost => "and lost",
+            }
+        ),
+    );
+    drop(app);
+    log.await;
+
+    Ok(reply)
 }
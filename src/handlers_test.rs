@@ -0,0 +1,200 @@
+use crate::handlers::*;
+use crate::wordle::*;
+use mobot::*;
+
+#[test]
+fn legend_only_on_first_board() {
+    let mut wordle = Wordle::new("hello".into()).unwrap();
+
+    let game = wordle.play_turn("bolle").unwrap();
+    assert!(render_game(&game, true, false).contains("right spot"));
+
+    let game = wordle.play_turn("belly").unwrap();
+    assert!(!render_game(&game, true, false).contains("right spot"));
+}
+
+#[test]
+fn spoilers_off() {
+    let mut wordle = Wordle::new("hello".into()).unwrap();
+
+    let game = wordle.play_turn("world").unwrap();
+    assert!(render_game(&game, true, false).contains("||"));
+    assert!(!render_game(&game, false, false).contains("||"));
+}
+
+/// Adjacent correct letters that spell a country code, like U and S, are kept apart
+/// so clients don't draw them as a flag.
+#[test]
+fn adjacent_correct_letters_not_flags() {
+    let mut wordle = Wordle::new("crust".into()).unwrap();
+    let game = wordle.play_turn("crust").unwrap();
+    let board = render_game(&game, true, false);
+
+    let is_indicator = |c: char| ('\u{1F1E6}'..='\u{1F1FF}').contains(&c);
+    let chars = board.chars().collect::<Vec<_>>();
+    // The five letters of the word, and the legend's.
+    assert_eq!(chars.iter().filter(|c| is_indicator(**c)).count(), 6);
+    assert!(
+        !chars
+            .windows(2)
+            .any(|w| is_indicator(w[0]) && is_indicator(w[1])),
+        "{}",
+        board
+    );
+}
+
+/// With plain letters, correct letters are shown without emoji.
+#[test]
+fn plain_letters() {
+    let mut wordle = Wordle::new("crust".into()).unwrap();
+    let game = wordle.play_turn("crust").unwrap();
+    let board = render_game(&game, true, true);
+
+    assert!(board.contains("`[U]` "), "{}", board);
+    assert!(!board
+        .chars()
+        .any(|c| ('\u{1F1E6}'..='\u{1F1FF}').contains(&c)));
+}
+
+#[test]
+fn describe_game_in_words() {
+    let mut wordle = Wordle::new("crane".into()).unwrap();
+    assert_eq!(describe_game(&wordle.game().unwrap()), "No guesses yet.");
+
+    wordle.play_turn("cater").unwrap();
+    let game = wordle.play_turn("crane").unwrap();
+    assert_eq!(
+        describe_game(&game),
+        "Guess 1: C correct, A present elsewhere, T absent, E present elsewhere, R present elsewhere.\n\
+         Guess 2: C correct, R correct, A correct, N correct, E correct.\n\
+         Letters tried: A, C, E, N, R, T."
+    );
+}
+
+#[test]
+fn quotes_stripped_from_guesses() {
+    assert_eq!(strip_quotes("\"crane\""), "crane");
+    assert_eq!(strip_quotes(" \u{201C}crane\u{201D} "), "crane");
+    assert_eq!(strip_quotes("`'crane'`"), "crane");
+    assert_eq!(strip_quotes("don't"), "don't");
+    assert_eq!(strip_quotes("goin'"), "goin'");
+    assert_eq!(strip_quotes("\""), "\"");
+}
+
+#[test]
+fn chat_ids_parsed() {
+    assert_eq!(parse_chat_id(" -100123 ").unwrap(), -100123);
+    assert_eq!(
+        parse_chat_id("").unwrap_err().to_string(),
+        "a chat ID is needed"
+    );
+    assert_eq!(
+        parse_chat_id("0").unwrap_err().to_string(),
+        "0 is not a chat ID"
+    );
+    assert_eq!(
+        parse_chat_id("99999999999999999999")
+            .unwrap_err()
+            .to_string(),
+        "99999999999999999999 is too large for a chat ID"
+    );
+    assert!(parse_chat_id("../42").is_err());
+}
+
+#[test]
+fn celebrations_by_guesses() {
+    for polite in [false, true] {
+        let first = celebration_for(1, 6, polite);
+        let middle = celebration_for(3, 6, polite);
+        let last = celebration_for(6, 6, polite);
+        assert_ne!(first, middle);
+        assert_ne!(middle, last);
+        assert_ne!(first, last);
+        assert_eq!(celebration_for(4, 6, polite), middle);
+    }
+
+    // The tone setting picks the words.
+    assert_ne!(celebration_for(1, 6, false), celebration_for(1, 6, true));
+    assert_eq!(celebration_for(4, 4, true), "Phew, just in time!");
+}
+
+#[test]
+fn comments_get_ruder_with_fewer_attempts() {
+    // Guess 1 leaves 5 attempts, guess 5 leaves 1.
+    assert_ne!(try_again_comment(5, false), try_again_comment(1, false));
+
+    // Polite players are spared.
+    assert_eq!(try_again_comment(5, true), try_again_comment(1, true));
+}
+
+#[test]
+fn greetings_by_hour() {
+    assert_eq!(greeting_for(0), "Good evening");
+    assert_eq!(greeting_for(5), "Good morning");
+    assert_eq!(greeting_for(11), "Good morning");
+    assert_eq!(greeting_for(12), "Good afternoon");
+    assert_eq!(greeting_for(17), "Good afternoon");
+    assert_eq!(greeting_for(18), "Good evening");
+    assert_eq!(greeting_for(23), "Good evening");
+}
+
+/// Messages without text get a friendly reply instead of a handler error.
+#[test]
+fn user_errors_are_replies() {
+    let message = api::Message {
+        text: None,
+        ..Default::default()
+    };
+    let err = message_text(&message).unwrap_err();
+    assert_eq!(err, UserError::NoText);
+
+    match reply_to_user_errors(Err(err.into())) {
+        Ok(Action::ReplyText(reply)) => assert_eq!(
+            reply,
+            "Sorry, I can only read text. Please send a word as text."
+        ),
+        _ => panic!("expected a text reply"),
+    }
+
+    // Other errors are still handler errors.
+    assert!(reply_to_user_errors(Err(anyhow::anyhow!("boom"))).is_err());
+}
+
+#[test]
+fn uptime_formatting() {
+    assert_eq!(
+        format_uptime(std::time::Duration::from_secs(5)),
+        "0d 0h 0m 5s"
+    );
+    assert_eq!(
+        format_uptime(std::time::Duration::from_secs(2 * 86400 + 3 * 3600 + 61)),
+        "2d 3h 1m 1s"
+    );
+}
+
+#[test]
+fn letter_hints() {
+    assert_eq!(
+        letter_hint("HELLO"),
+        "The word has 4 different letters, and at least one of them repeats."
+    );
+    assert_eq!(
+        letter_hint("crane"),
+        "The word has 5 different letters, and none of them repeats."
+    );
+}
+
+/// With --debug-updates, messages are logged in full as JSON.
+#[test]
+fn format_update_as_json() {
+    let update = Update::Message(api::Message {
+        message_id: 7,
+        text: Some("/help debug-updates".into()),
+        ..Default::default()
+    });
+
+    let line = format_update(&update);
+    assert!(line.starts_with("Update: {"));
+    assert!(line.contains("\"message_id\":7"));
+    assert!(line.contains("\"text\":\"/help debug-updates\""));
+}
@@ -0,0 +1,182 @@
+/// The leaderboard ranks every player who has a save file. Reading the saves one at
+/// a time stalls the bot once there are many users, so they're read concurrently,
+/// with a bound on the number of files open at once.
+use std::{cmp::Ordering, sync::Arc};
+
+use anyhow::Context;
+use log::*;
+use serde::Deserialize;
+use tokio::{sync::Semaphore, task::JoinSet};
+
+use crate::app::{format_percentage, Score};
+use crate::saveformat::{self, SaveFormat};
+
+/// The number of save files read at once.
+pub const SCAN_CONCURRENCY: usize = 16;
+
+/// Entry is a single player's line on the leaderboard.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Entry {
+    pub user_id: String,
+    pub name: String,
+    pub handle: String,
+    pub score: Score,
+}
+
+/// SavedScore is the part of a user's save data the leaderboard needs.
+#[derive(Deserialize)]
+struct SavedScore {
+    user_id: String,
+    #[serde(default)]
+    user_handle: String,
+    #[serde(default)]
+    user_first_name: String,
+    score: Score,
+}
+
+impl Entry {
+    /// Returns the entry of a player. Players are named by handle, then first name,
+    /// then user ID.
+    pub fn new(user_id: String, handle: String, first_name: &str, score: Score) -> Entry {
+        let name = [handle.as_str(), first_name]
+            .into_iter()
+            .find(|n| !n.is_empty())
+            .unwrap_or(&user_id)
+            .to_string();
+
+        Entry {
+            user_id,
+            name,
+            handle,
+            score,
+        }
+    }
+}
+
+/// Parses a leaderboard entry from the contents of a user's save file in the given
+/// format.
+pub fn parse_save(format: SaveFormat, contents: &[u8]) -> anyhow::Result<Entry> {
+    let saved: SavedScore = format.parse(contents)?;
+    Ok(Entry::new(
+        saved.user_id,
+        saved.user_handle,
+        &saved.user_first_name,
+        saved.score,
+    ))
+}
+
+/// Returns the paths of the user save files in the save directory, in any save
+/// format.
+pub async fn save_files(save_dir: &str) -> anyhow::Result<Vec<String>> {
+    let mut entries = tokio::fs::read_dir(save_dir)
+        .await
+        .context(format!("Error reading directory {}", save_dir))?;
+
+    let mut paths = vec![];
+    while let Some(entry) = entries.next_entry().await? {
+        let name = entry.file_name().to_string_lossy().to_string();
+        if saveformat::save_user_id(&name).is_some() {
+            paths.push(format!("{}/{}", save_dir, name));
+        }
+    }
+
+    paths.sort();
+    Ok(paths)
+}
+
+/// Reads every user save in the save directory, at most `concurrency` at a time, and
+/// returns the ranked entries. Files that can't be read are skipped.
+pub async fn scan(save_dir: &str, concurrency: usize) -> anyhow::Result<Vec<Entry>> {
+    let permits = Arc::new(Semaphore::new(concurrency.max(1)));
+    let mut tasks = JoinSet::new();
+    for path in save_files(save_dir).await? {
+        let permits = Arc::clone(&permits);
+        tasks.spawn(async move {
+            let _permit = permits.acquire().await?;
+            let contents = tokio::fs::read(&path)
+                .await
+                .context(format!("Error reading file {}", path))?;
+            let format = SaveFormat::from_path(&path).unwrap_or_default();
+            parse_save(format, &contents).context(format!("Error deserializing file {}", path))
+        });
+    }
+
+    let mut entries = vec![];
+    while let Some(result) = tasks.join_next().await {
+        match result? {
+            Ok(entry) => entries.push(entry),
+            Err(e) => warn!("Skipping save file: {:#}", e),
+        }
+    }
+
+    rank(&mut entries);
+    Ok(entries)
+}
+
+/// Sorts entries by wins, then by win rate, then by user ID so ties are stable.
+pub fn rank(entries: &mut [Entry]) {
+    entries.sort_by(|a, b| {
+        b.score
+            .wins
+            .cmp(&a.score.wins)
+            .then_with(|| {
+                b.score
+                    .win_rate()
+                    .partial_cmp(&a.score.win_rate())
+                    .unwrap_or(Ordering::Equal)
+            })
+            .then_with(|| a.user_id.cmp(&b.user_id))
+    });
+}
+
+/// Returns the user's place by win rate among the players with at least `min_games`
+/// games, from 1, and the number of such players. Ties go to the player with more
+/// wins. Returns `None` if the user doesn't have enough games.
+pub fn win_rate_rank(entries: &[Entry], user_id: &str, min_games: u32) -> Option<(usize, usize)> {
+    let mut ranked = entries
+        .iter()
+        .filter(|e| e.score.games >= min_games.max(1))
+        .collect::<Vec<_>>();
+    ranked.sort_by(|a, b| {
+        b.score
+            .win_rate()
+            .partial_cmp(&a.score.win_rate())
+            .unwrap_or(Ordering::Equal)
+            .then_with(|| b.score.wins.cmp(&a.score.wins))
+            .then_with(|| a.user_id.cmp(&b.user_id))
+    });
+
+    let place = ranked.iter().position(|e| e.user_id == user_id)?;
+    Some((place + 1, ranked.len()))
+}
+
+/// Returns the entries as CSV, with a header row. The win rate is a percentage with
+/// one decimal, leaving out forgiven losses.
+pub fn to_csv(entries: &[Entry]) -> String {
+    let mut csv = String::from("id,handle,games,wins,win%\n");
+    for entry in entries {
+        let rate = format_percentage(
+            entry.score.wins,
+            entry.score.games.saturating_sub(entry.score.forgiven),
+            1,
+        );
+        csv.push_str(&format!(
+            "{},{},{},{},{}\n",
+            csv_field(&entry.user_id),
+            csv_field(&entry.handle),
+            entry.score.games,
+            entry.score.wins,
+            rate.trim_end_matches('%')
+        ));
+    }
+    csv
+}
+
+/// Quotes a CSV field if it has a comma, quote or line break in it.
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.into()
+    }
+}
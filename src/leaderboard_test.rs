@@ -0,0 +1,65 @@
+use crate::leaderboard::*;
+use crate::saveformat::SaveFormat;
+
+/// Reads the saves one at a time, without the async scan.
+fn sequential_scan(save_dir: &std::path::Path) -> Vec<Entry> {
+    let mut entries = std::fs::read_dir(save_dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| {
+            path.file_stem()
+                .is_some_and(|id| id.to_string_lossy().parse::<i64>().is_ok())
+        })
+        .filter_map(|path| parse_save(SaveFormat::Json, &std::fs::read(path).unwrap()).ok())
+        .collect::<Vec<_>>();
+    rank(&mut entries);
+    entries
+}
+
+#[tokio::test]
+async fn concurrent_scan_matches_sequential() {
+    let dir = std::env::temp_dir().join("wordlebot-leaderboard-scan");
+    _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+
+    for id in 0..100 {
+        std::fs::write(
+            dir.join(format!("{}.json", id)),
+            format!(
+                r#"{{"user_id": "{}", "user_handle": "user{}", "score": {{"games": {}, "wins": {}}}}}"#,
+                id,
+                id,
+                id % 13 + 5,
+                id % 7
+            ),
+        )
+        .unwrap();
+    }
+
+    // Files that aren't user saves, or can't be parsed, are skipped.
+    std::fs::write(dir.join("admin.json"), r#"{"admin_handle": "admin"}"#).unwrap();
+    std::fs::write(dir.join("season-1.json"), "{}").unwrap();
+    std::fs::write(dir.join("1000.json"), "not json").unwrap();
+
+    let entries = scan(&dir.to_string_lossy(), 8).await.unwrap();
+    assert_eq!(entries.len(), 100);
+    assert_eq!(entries, sequential_scan(&dir));
+    assert_eq!(entries[0].score.wins, 6);
+}
+
+#[test]
+fn names_fall_back_to_first_name_and_id() {
+    let entry = parse_save(
+        SaveFormat::Json,
+        br#"{"user_id": "42", "user_first_name": "Q", "score": {"games": 1, "wins": 1}}"#,
+    )
+    .unwrap();
+    assert_eq!(entry.name, "Q");
+
+    let entry = parse_save(
+        SaveFormat::Json,
+        br#"{"user_id": "42", "score": {"games": 1, "wins": 1}}"#,
+    )
+    .unwrap();
+    assert_eq!(entry.name, "42");
+}
@@ -1,7 +1,10 @@
-use std::{collections::HashSet, path::Path};
+use std::{path::Path, sync::Arc, time::Duration};
 
 use crate::app::*;
 use crate::handlers::*;
+use crate::saveformat::SaveFormat;
+use crate::wordle::HardMode;
+use crate::words::*;
 
 use anyhow::Context;
 use argh::FromArgs;
@@ -10,8 +13,20 @@ use mobot::*;
 use rand::seq::SliceRandom;
 
 mod app;
+mod card;
+mod challenge;
+mod commands;
+mod grid;
 mod handlers;
+mod leaderboard;
+mod saveformat;
+mod season;
+mod selftest;
+mod settings;
+mod solver;
 mod wordle;
+mod words;
+mod wordstats;
 
 #[derive(FromArgs)]
 /// wordlebot is a Telegram bot that plays Wordle.
@@ -28,9 +43,16 @@ struct Args {
     #[argh(option, short = 't', default = "String::from(\"target_words.txt\")")]
     target_words: String,
 
-    /// file containing valid words for the bot, one per line
-    #[argh(option, short = 'v', default = "String::from(\"valid_words.txt\")")]
-    valid_words: String,
+    /// file containing valid words for the bot, one per line. May be repeated to
+    /// merge several dictionaries. Defaults to valid_words.txt.
+    #[argh(option, short = 'v')]
+    valid_words: Vec<String>,
+
+    /// file containing both target and valid words, each followed by T for a target
+    /// or V for a valid-only word, e.g. "crane T". Replaces -t, and -v files are
+    /// merged in if given.
+    #[argh(option)]
+    combined_words: Option<String>,
 
     /// directory to save user state. If empty, state is not saved.
     #[argh(option, short = 's')]
@@ -39,23 +61,222 @@ struct Args {
     /// authorized username for admin functions. If empty, no admin functions.
     #[argh(option, short = 'a')]
     admin_username: Option<String>,
+
+    /// seconds to hold back saves so repeated writes per user coalesce. If zero,
+    /// state is saved immediately.
+    #[argh(option, default = "0")]
+    save_delay_s: u64,
+
+    /// format of user saves: json or msgpack. Saves in either format are read.
+    /// Defaults to json.
+    #[argh(option, default = "SaveFormat::Json")]
+    save_format: SaveFormat,
+
+    /// number of chats whose games are kept in memory. Past this, the games of the
+    /// least recently active chats are saved and dropped, and reloaded on the chat's
+    /// next message. Needs --save-dir. Unlimited by default.
+    #[argh(option)]
+    max_games: Option<usize>,
+
+    /// play the message that implicitly starts a game as the first guess.
+    #[argh(switch)]
+    first_message_counts: bool,
+
+    /// leave target words out of admin logs. Admins can still /peek at a chat's target.
+    #[argh(switch)]
+    hide_targets: bool,
+
+    /// length of a season in days. Season scores are archived and reset at the end
+    /// of each season. If unset, there are no seasons.
+    #[argh(option)]
+    season_days: Option<u64>,
+
+    /// avoid serving a target word that's one letter off the previous target.
+    #[argh(switch)]
+    avoid_similar_targets: bool,
+
+    /// whether to forward players' events to the admin log unless they opt out with
+    /// /analytics.
+    #[argh(option, default = "true")]
+    analytics_default: bool,
+
+    /// number of games a player needs before their win rate is shown. Until then,
+    /// only wins and games are shown.
+    #[argh(option, default = "0")]
+    min_games_for_rate: u32,
+
+    /// only send game starts, wins and losses to the admin log, skipping
+    /// intermediate turns.
+    #[argh(switch)]
+    quiet_admin: bool,
+
+    /// an extra game mode with its own target words, as NAME=FILE. Players start it
+    /// with /newNAME. May be repeated.
+    #[argh(option)]
+    mode: Vec<String>,
+
+    /// file of word frequencies, with a word and its count on each line.
+    #[argh(option)]
+    word_frequencies: Option<String>,
+
+    /// forgive losses to target words with a frequency below this, so they don't
+    /// count against the win rate. Needs --word-frequencies.
+    #[argh(option)]
+    mercy_frequency: Option<u64>,
+
+    /// reject guesses in which a letter appears more than once.
+    #[argh(switch)]
+    no_repeated_letters: bool,
+
+    /// skip target words that aren't in the valid-word files, instead of making them
+    /// valid guesses.
+    #[argh(switch)]
+    strict_targets: bool,
+
+    /// seconds after a win during which plain messages don't start a new game.
+    /// Players can still use /new.
+    #[argh(option, default = "0")]
+    post_win_cooldown_s: u64,
+
+    /// milliseconds within which a repeat of a chat's previous message is ignored,
+    /// so a double-sent guess doesn't use up two attempts. If zero, repeats are
+    /// always played.
+    #[argh(option, default = "2000")]
+    duplicate_window_ms: u64,
+
+    /// use ASCII instead of emoji in boards and replies, for terminals and logs
+    /// that can't show them.
+    #[argh(switch)]
+    no_emoji: bool,
+
+    /// whether a game given up with /giveup counts as a lost game. If false, it
+    /// doesn't count toward the score at all.
+    #[argh(option, default = "true")]
+    giveup_counts: bool,
+
+    /// the bot's offset from UTC in hours, e.g. -5. If set, welcome messages greet
+    /// players by time of day.
+    #[argh(option)]
+    utc_offset_h: Option<i32>,
+
+    /// the emoji shown when a player wins. Defaults to dancers.
+    #[argh(option)]
+    win_emoji: Option<String>,
+
+    /// the emoji shown when a player loses. Defaults to a face holding back tears.
+    #[argh(option)]
+    lose_emoji: Option<String>,
+
+    /// let guesses match letters regardless of accents, so CAFE wins against CAFÉ.
+    #[argh(switch)]
+    accent_insensitive: bool,
+
+    /// make players use the hints from earlier attempts: strict, or after-green to
+    /// only enforce them once a letter is in the right spot. Defaults to off.
+    #[argh(option, default = "HardMode::Off")]
+    hard_mode: HardMode,
+
+    /// log every incoming update in full. Updates hold players' messages and
+    /// names, so this is for debugging only.
+    #[argh(switch)]
+    debug_updates: bool,
+
+    /// seconds each long poll for updates waits before returning empty. Lower
+    /// values react faster to shutdown at the cost of more requests.
+    #[argh(option, default = "60")]
+    poll_timeout_s: u64,
+
+    /// announce the top players of a season to the admin chat when it ends.
+    #[argh(switch)]
+    season_announce: bool,
+
+    /// hours a game can be left alone before its player is reminded about it.
+    /// Players opt in with /reminders. If unset, there are no reminders.
+    #[argh(option)]
+    reminder_after_h: Option<u64>,
+
+    /// leave the target word out of the reply to a lost game, until an admin sends
+    /// /reveal.
+    #[argh(switch)]
+    hide_word_on_loss: bool,
+
+    /// the longest target word to pick, at most 12 letters. Longer words in the
+    /// target lists are skipped.
+    #[argh(option, default = "wordle::MAX_WORD_LENGTH")]
+    max_word_length: usize,
+
+    /// replay a game as TARGET:GUESS,GUESS,... and print the board, without
+    /// connecting to Telegram.
+    #[argh(option, hidden_help)]
+    selftest: Option<String>,
+
+    /// load and check the word lists, print a report, and exit without connecting
+    /// to Telegram.
+    #[argh(switch)]
+    validate_only: bool,
 }
 
-// read_words reads a file containing one word per line, and returns a vector of
-// strings. It filters out empty lines and lines that start with a '#'.
-fn read_words(path: impl AsRef<str>) -> Vec<String> {
-    std::fs::read_to_string(path.as_ref())
-        .unwrap_or_default()
-        .lines()
-        .map(String::from)
-        .filter(|s| !s.starts_with("#"))
-        .filter(|s| !s.trim().is_empty())
-        .collect()
+/// The longest game name that fits comfortably in welcome and help messages.
+const MAX_GAME_NAME_LEN: usize = 64;
+
+/// The range of poll timeouts, in seconds. Zero would poll in a busy loop, and long
+/// polls beyond a few minutes tend to be cut off by proxies.
+const POLL_TIMEOUT_RANGE: std::ops::RangeInclusive<u64> = 1..=300;
+
+/// Checks that the poll timeout is within POLL_TIMEOUT_RANGE.
+fn validate_poll_timeout(poll_timeout_s: u64) -> anyhow::Result<u64> {
+    if !POLL_TIMEOUT_RANGE.contains(&poll_timeout_s) {
+        anyhow::bail!(
+            "Poll timeout must be between {} and {} seconds.",
+            POLL_TIMEOUT_RANGE.start(),
+            POLL_TIMEOUT_RANGE.end()
+        );
+    }
+
+    Ok(poll_timeout_s)
 }
 
 async fn start(args: Args) -> anyhow::Result<()> {
-    // Read the list of target words.
-    let mut target_words = read_words(args.target_words);
+    // Replay a game for debugging, and exit.
+    if let Some(spec) = &args.selftest {
+        let (target, guesses) = selftest::parse_spec(spec)?;
+        print!("{}", selftest::run(&target, &guesses)?);
+        return Ok(());
+    }
+
+    // Make sure the game name fits in messages.
+    let game_name = args.game_name.trim().to_string();
+    if game_name.is_empty() || game_name.chars().count() > MAX_GAME_NAME_LEN {
+        anyhow::bail!(
+            "Game name must be between 1 and {} characters long.",
+            MAX_GAME_NAME_LEN
+        );
+    }
+    let poll_timeout_s = validate_poll_timeout(args.poll_timeout_s)?;
+
+    // Read the target and valid words concurrently, as valid-word lists can be large.
+    // A combined file holds both, and other valid-word files are optional.
+    let mut valid_word_files = args.valid_words;
+    if valid_word_files.is_empty() && args.combined_words.is_none() {
+        valid_word_files.push("valid_words.txt".into());
+    }
+    let (mut target_words, valid_words) = match &args.combined_words {
+        Some(path) => {
+            let ((targets, valid_only), mut valid_words) = tokio::join!(
+                load_combined_words(path),
+                load_valid_words(&valid_word_files, &[])
+            );
+            valid_words.extend(valid_only);
+            add_targets(&mut valid_words, &targets);
+            (targets, valid_words)
+        }
+        None => {
+            tokio::join!(
+                load_words(&args.target_words),
+                load_valid_words(&valid_word_files, &[])
+            )
+        }
+    };
     if target_words.is_empty() {
         anyhow::bail!("No target words found.");
     }
@@ -63,24 +284,108 @@ async fn start(args: Args) -> anyhow::Result<()> {
     // Shuffle the target words.
     target_words.shuffle(&mut rand::thread_rng());
 
-    // Read the list of valid words, and make sure the target words are in it.
-    let mut valid_words = HashSet::from_iter(read_words(args.valid_words).iter().cloned());
-    target_words.iter().for_each(|w| {
-        valid_words.insert(w.to_ascii_lowercase());
-    });
+    // Read the target words of each game mode.
+    let mut modes = vec![];
+    for mode in &args.mode {
+        let (name, path) = mode
+            .split_once('=')
+            .context(format!("Invalid mode {}, expected NAME=FILE.", mode))?;
+        let words = load_words(path).await;
+        if words.is_empty() {
+            anyhow::bail!("No target words found for mode {}.", name);
+        }
+        modes.push((name.to_string(), words));
+    }
+
+    // Report on the word lists, and exit. This doesn't need a Telegram token.
+    if args.validate_only {
+        print!(
+            "{}",
+            validate_word_lists(&target_words, &valid_words, &modes)
+        );
+        return Ok(());
+    }
 
+    // The target words are added to the valid words by the app, so they're valid
+    // guesses unless --strict-targets skips them.
     if valid_words.is_empty() {
-        error!("No valid words found. Not validating words.");
+        error!("No valid words found. Only target words are valid guesses.");
     }
 
     if !Path::new(&args.save_dir.clone().unwrap_or_default()).exists() {
         error!("Save directory does not exist. Not saving state.");
     }
+    if args.max_games.is_some() && args.save_dir.is_none() {
+        error!("--max-games needs --save-dir. Not dropping games from memory.");
+    }
 
     // Initialize the bot app state.
-    let mut app = App::new(args.game_name, target_words);
+    let mut app = App::new(game_name, target_words);
+    for (name, mut words) in modes {
+        words.shuffle(&mut rand::thread_rng());
+        app.add_mode(name, words);
+    }
     app.set_save_dir(args.save_dir.unwrap_or_default());
+    app.set_save_delay(Duration::from_secs(args.save_delay_s));
+    app.set_save_format(args.save_format);
+    app.set_max_games(args.max_games);
     app.set_valid_words(valid_words);
+    app.set_word_files(args.target_words, valid_word_files);
+    app.set_combined_words_file(args.combined_words);
+    app.set_first_message_counts(args.first_message_counts);
+    app.set_hide_targets(args.hide_targets);
+    app.set_hide_word_on_loss(args.hide_word_on_loss);
+    app.set_max_word_length(args.max_word_length)?;
+    app.set_analytics_default(args.analytics_default);
+    app.set_min_games_for_rate(args.min_games_for_rate);
+    app.set_quiet_admin(args.quiet_admin);
+    app.set_no_repeated_letters(args.no_repeated_letters);
+    app.set_strict_targets(args.strict_targets);
+    app.set_post_win_cooldown(Duration::from_secs(args.post_win_cooldown_s));
+    app.set_duplicate_window(Duration::from_millis(args.duplicate_window_ms));
+    app.set_no_emoji(args.no_emoji);
+    app.set_giveup_counts(args.giveup_counts);
+    app.set_utc_offset(args.utc_offset_h);
+    app.set_celebration_emoji(args.win_emoji, args.lose_emoji)?;
+    app.set_mercy(
+        args.word_frequencies
+            .map(read_frequencies)
+            .unwrap_or_default(),
+        args.mercy_frequency,
+    );
+    app.set_avoid_similar_targets(args.avoid_similar_targets);
+    app.set_accent_insensitive(args.accent_insensitive);
+    app.set_hard_mode(args.hard_mode);
+    app.set_debug_updates(args.debug_updates);
+    app.set_season_announce(args.season_announce);
+    app.set_reminder_after(
+        args.reminder_after_h
+            .map(|h| Duration::from_secs(h * 60 * 60)),
+    );
+    app.spawn_save_flusher();
+
+    // Load the current season.
+    app.set_season_length(
+        args.season_days
+            .map(|d| Duration::from_secs(d * 24 * 60 * 60)),
+    );
+    if app.has_seasons() {
+        if let Err(e) = app.load_season().await {
+            error!("Could not load season: {}", e);
+        }
+    }
+
+    // Load the word stats.
+    if let Err(e) = app.load_word_stats().await {
+        warn!("Starting new word stats: {}", e);
+    }
+
+    // Load the words held back from lost games.
+    if app.hide_word_on_loss() {
+        if let Err(e) = app.load_held_back_words().await {
+            warn!("No held back words: {}", e);
+        }
+    }
 
     // Load the admin save data.
     if let Err(e) = app.load_admin(args.admin_username).await {
@@ -94,28 +399,28 @@ async fn start(args: Args) -> anyhow::Result<()> {
             .into(),
     );
 
+    // Setup router. Keep a handle on the app so pending saves can be flushed on
+    // shutdown.
+    let mut router = Router::new(client)
+        .with_state(app.clone())
+        .with_poll_timeout_s(poll_timeout_s as i64);
+
+    // Rotate seasons and send reminders in the background. Both send messages, so
+    // they need the API.
+    app.spawn_season_rotation(Arc::clone(&router.api));
+    app.spawn_reminders(Arc::clone(&router.api));
+
     // Register bot commands for the Telegram menu
-    let commands = vec![
-        api::BotCommand {
-            command: "/help".into(),
-            description: "Show help".into(),
-        },
-        api::BotCommand {
-            command: "/new".into(),
-            description: "New game".into(),
-        },
-        api::BotCommand {
-            command: "/score".into(),
-            description: "Show my score".into(),
-        },
-    ];
-
-    // Setup router
-    let mut router = Router::new(client).with_state(app);
     router
         .api
         .set_my_commands(&api::SetMyCommandsRequest {
-            commands,
+            commands: commands::bot_commands()
+                .into_iter()
+                .chain(app.mode_names().iter().map(|name| api::BotCommand {
+                    command: format!("/new{}", name),
+                    description: format!("New {} game", name),
+                }))
+                .collect(),
             ..Default::default()
         })
         .await
@@ -128,10 +433,18 @@ async fn start(args: Args) -> anyhow::Result<()> {
             handle_bot_command,
         )
         .add_route(Route::Message(Matcher::Any), handle_chat_event)
-        .start()
-        .await;
+        // Routes only see messages with text, unless they match photos or files.
+        // Those get a reply asking for text.
+        .add_route(Route::Message(Matcher::Photo), handle_chat_event)
+        .add_route(Route::Message(Matcher::Document), handle_chat_event)
+        .add_route(Route::EditedMessage(Matcher::Any), handle_edited_message);
 
-    Ok(())
+    tokio::select! {
+        _ = router.start() => {}
+        _ = tokio::signal::ctrl_c() => info!("Shutting down..."),
+    }
+
+    app.flush_saves().await
 }
 
 #[tokio::main]
@@ -151,3 +464,30 @@ mod wordle_test;
 
 #[cfg(test)]
 mod app_test;
+
+#[cfg(test)]
+mod card_test;
+
+#[cfg(test)]
+mod commands_test;
+
+#[cfg(test)]
+mod main_test;
+
+#[cfg(test)]
+mod grid_test;
+
+#[cfg(test)]
+mod handlers_test;
+
+#[cfg(test)]
+mod leaderboard_test;
+
+#[cfg(test)]
+mod selftest_test;
+
+#[cfg(test)]
+mod solver_test;
+
+#[cfg(test)]
+mod words_test;
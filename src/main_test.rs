@@ -0,0 +1,43 @@
+use crate::*;
+
+#[test]
+fn poll_timeout_range() {
+    assert_eq!(validate_poll_timeout(1).unwrap(), 1);
+    assert_eq!(validate_poll_timeout(60).unwrap(), 60);
+    assert!(validate_poll_timeout(0).is_err());
+    assert!(validate_poll_timeout(301).is_err());
+}
+
+#[test]
+fn poll_timeout_flag() {
+    let args: Args = Args::from_args(&["wordlebot"], &["--poll-timeout-s", "5"]).unwrap();
+    assert_eq!(args.poll_timeout_s, 5);
+
+    let args: Args = Args::from_args(&["wordlebot"], &[]).unwrap();
+    assert_eq!(args.poll_timeout_s, 60);
+}
+
+/// --validate-only loads the word lists and exits without a Telegram token.
+#[tokio::test]
+async fn validate_only_needs_no_token() {
+    let dir = std::env::temp_dir().join("wordlebot-validate-only");
+    std::fs::create_dir_all(&dir).unwrap();
+    let targets = dir.join("targets.txt");
+    let valid = dir.join("valid.txt");
+    std::fs::write(&targets, "hello\ncrane\n").unwrap();
+    std::fs::write(&valid, "hello\ncrane\nworld\n").unwrap();
+
+    std::env::remove_var("TELEGRAM_TOKEN");
+    let args: Args = Args::from_args(
+        &["wordlebot"],
+        &[
+            "--validate-only",
+            "-t",
+            &targets.to_string_lossy(),
+            "-v",
+            &valid.to_string_lossy(),
+        ],
+    )
+    .unwrap();
+    start(args).await.unwrap();
+}
@@ -0,0 +1,80 @@
+/// Save formats are the file formats user saves are written in. JSON is the default,
+/// and MessagePack is smaller and faster on busy bots. Saves are read in whichever
+/// format their extension names, so switching formats keeps existing saves.
+use anyhow::Context;
+use serde::{de::DeserializeOwned, Serialize};
+
+/// SaveFormat is the file format of a user save.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum SaveFormat {
+    #[default]
+    Json,
+    MessagePack,
+}
+
+impl SaveFormat {
+    /// Every save format, in the order saves are looked for.
+    pub const ALL: [SaveFormat; 2] = [SaveFormat::Json, SaveFormat::MessagePack];
+
+    /// Returns the extension of save files in this format, without the dot.
+    pub fn extension(self) -> &'static str {
+        match self {
+            SaveFormat::Json => "json",
+            SaveFormat::MessagePack => "msgpack",
+        }
+    }
+
+    /// Returns the format named by the file name's extension, if any.
+    pub fn from_path(path: &str) -> Option<SaveFormat> {
+        let (_, extension) = path.rsplit_once('.')?;
+        SaveFormat::ALL
+            .into_iter()
+            .find(|f| f.extension() == extension)
+    }
+
+    /// Serializes the value in this format.
+    pub fn serialize<T: Serialize>(self, value: &T) -> anyhow::Result<Vec<u8>> {
+        match self {
+            SaveFormat::Json => serde_json::to_vec(value).context("Error serializing JSON"),
+            // Structs are written as maps, so fields can be added and skipped like in
+            // JSON.
+            SaveFormat::MessagePack => {
+                rmp_serde::to_vec_named(value).context("Error serializing MessagePack")
+            }
+        }
+    }
+
+    /// Deserializes a value from the contents of a file in this format.
+    pub fn parse<T: DeserializeOwned>(self, contents: &[u8]) -> anyhow::Result<T> {
+        match self {
+            SaveFormat::Json => serde_json::from_slice(contents).context("Error parsing JSON"),
+            SaveFormat::MessagePack => {
+                rmp_serde::from_slice(contents).context("Error parsing MessagePack")
+            }
+        }
+    }
+}
+
+impl std::str::FromStr for SaveFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "json" => Ok(SaveFormat::Json),
+            "msgpack" => Ok(SaveFormat::MessagePack),
+            _ => Err(format!(
+                "unknown save format {}, expected json or msgpack",
+                s
+            )),
+        }
+    }
+}
+
+/// Returns the user ID a save file is named after, or `None` if the file isn't a
+/// user save. Only user saves are named after (numeric) user IDs.
+pub fn save_user_id(name: &str) -> Option<&str> {
+    let format = SaveFormat::from_path(name)?;
+    name.strip_suffix(format.extension())
+        .and_then(|n| n.strip_suffix('.'))
+        .filter(|id| id.parse::<i64>().is_ok())
+}
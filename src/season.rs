@@ -0,0 +1,104 @@
+/// Seasons group scores into fixed-length periods, so operators can run weekly
+/// leaderboards alongside all-time totals. When a season ends, its scores are
+/// archived to `season-<number>.json` in the save directory.
+use std::{
+    collections::HashMap,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use anyhow::Context;
+use serde::{Deserialize, Serialize};
+
+use crate::app::Score;
+
+/// Season represents the season currently being played. It's saved as `season.json`.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Season {
+    pub number: u32,
+
+    /// Unix time (in seconds) at which the season started.
+    pub started_at: u64,
+}
+
+impl Default for Season {
+    fn default() -> Self {
+        Season {
+            number: 1,
+            started_at: now(),
+        }
+    }
+}
+
+/// SeasonArchive is a snapshot of the scores of a finished season.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SeasonArchive {
+    pub number: u32,
+    pub started_at: u64,
+    pub ended_at: u64,
+
+    /// Season scores keyed by user ID.
+    pub scores: HashMap<String, Score>,
+}
+
+/// Returns the current Unix time in seconds.
+pub fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
+/// Loads the current season from the save directory.
+pub async fn load_season(save_dir: &str) -> anyhow::Result<Season> {
+    let filename = format!("{}/season.json", save_dir);
+    let contents = tokio::fs::read(&filename)
+        .await
+        .context(format!("Error reading file {}", filename))?;
+
+    serde_json::from_slice(&contents).context(format!("Error deserializing file {}", filename))
+}
+
+/// Saves the current season to the save directory.
+pub async fn save_season(save_dir: &str, season: &Season) -> anyhow::Result<()> {
+    let filename = format!("{}/season.json", save_dir);
+    tokio::fs::write(
+        &filename,
+        serde_json::to_vec(season).context("Error serializing season")?,
+    )
+    .await
+    .context(format!("Error writing file {}", filename))
+}
+
+/// Writes the archive of a finished season to the save directory.
+pub async fn write_archive(save_dir: &str, archive: &SeasonArchive) -> anyhow::Result<()> {
+    let filename = format!("{}/season-{}.json", save_dir, archive.number);
+    tokio::fs::write(
+        &filename,
+        serde_json::to_vec(archive).context("Error serializing season archive")?,
+    )
+    .await
+    .context(format!("Error writing file {}", filename))
+}
+
+/// Returns the numbers of all archived seasons in the save directory, in ascending
+/// order.
+pub async fn list_archives(save_dir: &str) -> anyhow::Result<Vec<u32>> {
+    let mut entries = tokio::fs::read_dir(save_dir)
+        .await
+        .context(format!("Error reading directory {}", save_dir))?;
+
+    let mut numbers = vec![];
+    while let Some(entry) = entries.next_entry().await? {
+        let name = entry.file_name().to_string_lossy().to_string();
+        if let Some(number) = name
+            .strip_prefix("season-")
+            .and_then(|n| n.strip_suffix(".json"))
+            .and_then(|n| n.parse().ok())
+        {
+            numbers.push(number);
+        }
+    }
+
+    numbers.sort();
+    Ok(numbers)
+}
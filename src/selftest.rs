@@ -0,0 +1,43 @@
+/// The self-test replays a game from a target word and a list of guesses, and renders
+/// the board as players would see it. It reproduces rendering bugs from bug reports
+/// without a Telegram client: `wordlebot --selftest HELLO:CRANE,HELLO`.
+use anyhow::Context;
+
+use crate::handlers::render_game;
+use crate::wordle::Wordle;
+
+/// Parses a self-test spec of the form `TARGET:GUESS,GUESS,...`.
+pub fn parse_spec(spec: &str) -> anyhow::Result<(String, Vec<String>)> {
+    let (target, guesses) = spec.split_once(':').context(format!(
+        "Invalid self-test {}, expected TARGET:GUESS,...",
+        spec
+    ))?;
+
+    Ok((
+        target.trim().to_string(),
+        guesses
+            .split(',')
+            .map(|g| g.trim().to_string())
+            .filter(|g| !g.is_empty())
+            .collect(),
+    ))
+}
+
+/// Plays the guesses against the target, and returns the board after the last
+/// accepted guess followed by the final game state. Rejected guesses are listed with
+/// the reason, and play continues.
+pub fn run(target: &str, guesses: &[String]) -> anyhow::Result<String> {
+    let mut wordle = Wordle::new(target.to_string())?;
+
+    let mut report = String::new();
+    for guess in guesses {
+        if let Err(e) = wordle.play_turn(guess) {
+            report.push_str(&format!("Rejected {}: {}\n", guess, e));
+        }
+    }
+
+    let game = wordle.game()?;
+    report.push_str(&render_game(&game, true, false));
+    report.push_str(&format!("State: {:?}\n", game.state));
+    Ok(report)
+}
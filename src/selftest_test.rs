@@ -0,0 +1,29 @@
+use crate::selftest::*;
+
+#[test]
+fn parses_spec() {
+    assert_eq!(
+        parse_spec("hello: crane, hello,").unwrap(),
+        (
+            "hello".to_string(),
+            vec!["crane".to_string(), "hello".to_string()]
+        )
+    );
+    assert!(parse_spec("hello").is_err());
+}
+
+/// A known sequence renders a stable board, so rendering changes show up here.
+#[test]
+fn snapshot() {
+    let (target, guesses) = parse_spec("HELLO:WORLD,HI,HELLO").unwrap();
+    assert_eq!(
+        run(&target, &guesses).unwrap(),
+        concat!(
+            "Rejected HI: word must be 5 characters long\n",
+            "Your attempts:\n\n",
+            " || ~W~ ||   * `O` *   || ~R~ ||   \u{1F1F1} || ~D~ ||  \n\n",
+            " \u{1F1ED} \u{1F1EA} \u{1F1F1} \u{1F1F1} \u{1F1F4}\n\n",
+            "State: Won\n"
+        )
+    );
+}
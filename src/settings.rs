@@ -0,0 +1,54 @@
+/// User settings are the preferences each player picks with commands like /tone,
+/// /spoilers and /tries. They're kept together, and saved as one object in the
+/// player's save data.
+use serde::{Deserialize, Serialize};
+
+/// UserSettings holds a player's preferences. Settings missing from a save take
+/// their default values.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct UserSettings {
+    /// The preferred target word length. If `None`, targets of any length are picked.
+    pub word_length: Option<usize>,
+
+    /// The number of guesses per game. If `None`, the player gets MAX_ATTEMPTS.
+    pub max_attempts: Option<usize>,
+
+    /// Whether the player's events are forwarded to the admin log. If `None`, the
+    /// bot's default applies.
+    pub analytics: Option<bool>,
+
+    /// Whether the bot's comments are polite rather than rude.
+    pub polite: bool,
+
+    /// Whether wrong letters are hidden behind spoilers.
+    pub spoilers: bool,
+
+    /// Whether correct letters are shown as plain letters rather than emoji.
+    pub plain_letters: bool,
+
+    /// Whether the player is reminded about games left alone.
+    pub reminders: bool,
+
+    /// Whether replies to guesses leave out the comments and the legend.
+    pub terse: bool,
+
+    /// Whether a correct letter is revealed once half the attempts are used.
+    pub assist: bool,
+}
+
+impl Default for UserSettings {
+    fn default() -> Self {
+        UserSettings {
+            word_length: None,
+            max_attempts: None,
+            analytics: None,
+            polite: false,
+            spoilers: true,
+            plain_letters: false,
+            reminders: false,
+            terse: false,
+            assist: false,
+        }
+    }
+}
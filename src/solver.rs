@@ -0,0 +1,154 @@
+/// Finds the words that are still possible given the attempts of a game.
+///
+/// Checking a candidate means assessing every attempt against it, which is slow
+/// across a whole dictionary. WordIndex precomputes a bitset of the letters in each
+/// word, so most candidates are ruled out with two mask comparisons before any
+/// assessment. On 20,000 random five-letter words after two guesses, the indexed
+/// filter runs a few hundred times faster than checking every word (see
+/// `cargo bench --bench solver`).
+///
+/// The same filter lets the bot solve a game itself, with `solve`.
+use crate::wordle::{assess_pair, Letter, State, Wordle, WordleError};
+
+/// Returns the bitset of letters in the word, with bit 0 for 'A'.
+fn letter_mask(word: &str) -> u32 {
+    word.chars()
+        .filter(|c| c.is_ascii_alphabetic())
+        .fold(0, |mask, c| {
+            mask | 1 << (c.to_ascii_uppercase() as u32 - 'A' as u32)
+        })
+}
+
+/// Returns true if the candidate would have produced the same assessments as the
+/// game's target word, for every attempt so far.
+pub fn is_consistent(wordle: &Wordle, candidate: &str) -> bool {
+    wordle.attempts.iter().all(|attempt| {
+        match (wordle.assess(attempt), assess_pair(candidate, attempt)) {
+            (Ok(actual), Ok(expected)) => actual == expected,
+            _ => false,
+        }
+    })
+}
+
+/// WordIndex is a dictionary with precomputed letter bitsets, built once at startup.
+#[derive(Debug, Default)]
+pub struct WordIndex {
+    words: Vec<String>,
+    masks: Vec<u32>,
+}
+
+impl WordIndex {
+    /// Builds the index. Words are uppercased.
+    pub fn new(words: impl IntoIterator<Item = String>) -> WordIndex {
+        let words = words
+            .into_iter()
+            .map(|w| w.to_uppercase())
+            .collect::<Vec<_>>();
+        let masks = words.iter().map(|w| letter_mask(w)).collect();
+        WordIndex { words, masks }
+    }
+
+    /// Returns true if the index has no words.
+    pub fn is_empty(&self) -> bool {
+        self.words.is_empty()
+    }
+
+    /// Returns the words that are still possible. Same result as checking each word
+    /// with `is_consistent`.
+    pub fn filter(&self, wordle: &Wordle) -> Vec<String> {
+        // Letters known to be in the word must be present. Letters that were only
+        // ever marked wrong must be absent.
+        let mut required = 0;
+        let mut wrong = 0;
+        for attempt in &wordle.attempts {
+            let Ok(letters) = wordle.assess(attempt) else {
+                continue;
+            };
+            for letter in letters {
+                match letter {
+                    Letter::Correct(c) | Letter::CorrectButWrongPosition(c) => {
+                        required |= letter_mask(&c.to_string())
+                    }
+                    Letter::Wrong(c) => wrong |= letter_mask(&c.to_string()),
+                }
+            }
+        }
+        let forbidden = wrong & !required;
+
+        self.words
+            .iter()
+            .zip(&self.masks)
+            .filter(|(w, mask)| {
+                w.len() == wordle.target_word.len()
+                    && *mask & required == required
+                    && *mask & forbidden == 0
+                    && is_consistent(wordle, w)
+            })
+            .map(|(w, _)| w.clone())
+            .collect()
+    }
+}
+
+/// Solution is how the solver played a game: its guesses in order, and whether the
+/// last one was the target.
+#[derive(Debug, PartialEq)]
+pub struct Solution {
+    pub guesses: Vec<String>,
+    pub won: bool,
+}
+
+/// Returns the candidate whose distinct letters appear in the most candidates, so
+/// its assessment splits them the most. Ties go to the first in alphabetical order.
+fn best_guess(candidates: &[String]) -> Option<&String> {
+    let mut counts = [0; 26];
+    for mask in candidates.iter().map(|w| letter_mask(w)) {
+        for (letter, count) in counts.iter_mut().enumerate() {
+            *count += (mask >> letter) & 1;
+        }
+    }
+
+    let score = |w: &str| {
+        let mask = letter_mask(w);
+        (0..26)
+            .filter(|l| (mask >> l) & 1 == 1)
+            .map(|l| counts[l])
+            .sum::<u32>()
+    };
+    candidates
+        .iter()
+        .max_by(|a, b| score(a).cmp(&score(b)).then_with(|| b.cmp(a)))
+}
+
+/// `solve` plays a game against the target using only the words in the index,
+/// guessing the most informative possible word each turn, within `max_attempts`.
+/// If the target isn't in the index, the solver runs out of words to try.
+pub fn solve(
+    index: &WordIndex,
+    target: &str,
+    max_attempts: usize,
+) -> Result<Solution, WordleError> {
+    let mut wordle = Wordle::new(target.to_string())?.with_max_attempts(max_attempts);
+    let mut guesses = vec![];
+
+    // Guesses that missed are never possible again, so they aren't repeated.
+    loop {
+        let candidates = index.filter(&wordle);
+        let Some(guess) = best_guess(&candidates) else {
+            return Ok(Solution {
+                guesses,
+                won: false,
+            });
+        };
+
+        guesses.push(guess.clone());
+        match wordle.play_turn(guess)?.state {
+            State::Playing => continue,
+            state => {
+                return Ok(Solution {
+                    guesses,
+                    won: state == State::Won,
+                })
+            }
+        }
+    }
+}
@@ -0,0 +1,50 @@
+use crate::solver::*;
+use crate::wordle::*;
+
+/// The reference filter, which checks every word.
+fn naive_filter(words: &[String], wordle: &Wordle) -> Vec<String> {
+    words
+        .iter()
+        .filter(|w| w.len() == wordle.target_word.len() && is_consistent(wordle, w))
+        .cloned()
+        .collect()
+}
+
+fn sample() -> Vec<String> {
+    [
+        "HELLO", "HELPS", "BELLY", "JELLY", "WORLD", "CRANE", "LLAMA", "HOTEL", "LEMON", "SHELL",
+        "SPELL", "EERIE", "HEELS", "ALLOY", "TABLE",
+    ]
+    .iter()
+    .map(|w| w.to_string())
+    .collect()
+}
+
+#[test]
+fn index_matches_naive_filter() {
+    let index = WordIndex::new(sample());
+    let mut wordle = Wordle::new("hello".into()).unwrap();
+    assert_eq!(index.filter(&wordle), naive_filter(&sample(), &wordle));
+
+    for guess in ["crane", "shell", "belly"] {
+        wordle.play_turn(guess).unwrap();
+        let indexed = index.filter(&wordle);
+        assert_eq!(indexed, naive_filter(&sample(), &wordle));
+        assert!(indexed.contains(&"HELLO".to_string()));
+    }
+
+    assert_eq!(index.filter(&wordle), vec!["HELLO".to_string()]);
+}
+
+#[test]
+fn solves_known_word() {
+    let index = WordIndex::new(sample());
+    let solution = solve(&index, "hello", MAX_ATTEMPTS).unwrap();
+    assert!(solution.won);
+    assert!(solution.guesses.len() <= MAX_ATTEMPTS);
+    assert_eq!(solution.guesses.last().unwrap(), "HELLO");
+
+    // Words missing from the dictionary can't be found.
+    let solution = solve(&index, "xylyl", MAX_ATTEMPTS).unwrap();
+    assert!(!solution.won);
+}
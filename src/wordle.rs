@@ -3,9 +3,104 @@
 /// guessed correctly, and which letters are in the word but in the wrong position.
 ///
 /// This module implements the game logic.
-use std::collections::HashMap;
-
 use serde::{Deserialize, Serialize};
+use unicode_normalization::{char::is_combining_mark, UnicodeNormalization};
+
+/// A safety limit on the number of attempts a game can hold, independent of when
+/// the game is lost, so a misbehaving chat can't grow a game without bound.
+pub const MAX_HISTORY: usize = 100;
+
+/// The longest target word a game accepts, as longer words don't fit on a phone
+/// screen.
+pub const MAX_WORD_LENGTH: usize = 12;
+
+/// The number of attempts a player gets unless they pick another limit.
+pub const MAX_ATTEMPTS: usize = 6;
+
+/// Returns MAX_ATTEMPTS, for games saved before the limit was configurable.
+pub fn default_max_attempts() -> usize {
+    MAX_ATTEMPTS
+}
+
+/// WordleError represents the ways a game can reject a word.
+#[derive(Debug, PartialEq)]
+pub enum WordleError {
+    /// The target word is too short to play.
+    TooShort,
+
+    /// The target word is longer than MAX_WORD_LENGTH letters.
+    TooLong,
+
+    /// The word has characters other than letters, e.g. spaces or hyphens.
+    NotLetters,
+
+    /// The word doesn't have as many letters as the target word.
+    WrongLength { expected: usize, got: usize },
+
+    /// The game was already won or lost.
+    GameOver,
+
+    /// The game has reached MAX_HISTORY attempts.
+    TooManyAttempts,
+
+    /// The word ignores a hint from an earlier attempt, which hard mode forbids.
+    HardMode(String),
+}
+
+impl std::fmt::Display for WordleError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            WordleError::TooShort => write!(f, "target_word must be at least 3 letters long"),
+            WordleError::TooLong => write!(
+                f,
+                "target_word must be at most {} letters long",
+                MAX_WORD_LENGTH
+            ),
+            WordleError::NotLetters => write!(f, "words must be letters only"),
+            WordleError::WrongLength { expected, .. } => {
+                write!(f, "word must be {} characters long", expected)
+            }
+            WordleError::GameOver => write!(f, "game is over"),
+            WordleError::TooManyAttempts => {
+                write!(f, "game has reached the limit of {} attempts", MAX_HISTORY)
+            }
+            WordleError::HardMode(reason) => write!(f, "{}", reason),
+        }
+    }
+}
+
+impl std::error::Error for WordleError {}
+
+/// HardMode is how strictly players must use the hints from earlier attempts.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum HardMode {
+    /// Hints are optional.
+    #[default]
+    Off,
+
+    /// Every hint must be used.
+    Strict,
+
+    /// Hints must be used once a letter has been placed correctly, which eases
+    /// players into hard mode.
+    AfterGreen,
+}
+
+impl std::str::FromStr for HardMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "off" => Ok(HardMode::Off),
+            "strict" => Ok(HardMode::Strict),
+            "after-green" => Ok(HardMode::AfterGreen),
+            _ => Err(format!(
+                "unknown hard mode {}, expected off, strict or after-green",
+                s
+            )),
+        }
+    }
+}
 
 /// State represents the current player state of a game.
 #[derive(Debug, Eq, PartialEq)]
@@ -30,6 +125,7 @@ pub enum Letter {
 pub struct Game {
     pub state: State,
     pub attempts: Vec<Vec<Letter>>,
+    pub max_attempts: usize,
 }
 
 impl Game {
@@ -50,6 +146,121 @@ impl Game {
         letters.dedup();
         letters
     }
+
+    /// `attempted_letters_in_order` returns a deduplicated vector of all the letters that
+    /// have been attempted so far, in the order they were first tried.
+    pub fn attempted_letters_in_order(&self) -> Vec<char> {
+        let mut letters = vec![];
+        for l in self.attempts.iter().flat_map(|a| a.iter()) {
+            let c = match l {
+                Letter::Correct(c) => *c,
+                Letter::CorrectButWrongPosition(c) => *c,
+                Letter::Wrong(c) => *c,
+            };
+            if !letters.contains(&c) {
+                letters.push(c);
+            }
+        }
+        letters
+    }
+}
+
+/// `within_one_edit` returns true if the words are at most one insertion, deletion or
+/// substitution apart.
+pub fn within_one_edit(a: &str, b: &str) -> bool {
+    let a = a.chars().collect::<Vec<_>>();
+    let b = b.chars().collect::<Vec<_>>();
+    let (short, long) = if a.len() <= b.len() { (a, b) } else { (b, a) };
+
+    match long.len() - short.len() {
+        0 => short.iter().zip(&long).filter(|(x, y)| x != y).count() <= 1,
+        1 => {
+            let prefix = short.iter().zip(&long).take_while(|(x, y)| x == y).count();
+            short[prefix..] == long[prefix + 1..]
+        }
+        _ => false,
+    }
+}
+
+/// `normalize` returns the form in which words are compared, so that a guess matches
+/// its target regardless of case or how its accents are encoded. If `fold_accents` is
+/// set, accents are dropped altogether.
+fn normalize(word: &str, fold_accents: bool) -> String {
+    let word = if fold_accents {
+        self::fold_accents(word)
+    } else {
+        word.nfc().collect()
+    };
+    word.to_uppercase()
+}
+
+/// `fold_accents` strips the accents from the letters of a word, e.g. "CAFÉ" becomes
+/// "CAFE".
+pub fn fold_accents(word: &str) -> String {
+    word.nfd()
+        .filter(|c| !is_combining_mark(*c))
+        .nfc()
+        .collect()
+}
+
+/// `assess_pair` compares the guess to the target word, and returns a vector of positional
+/// Letter instances. The vector is the same length as the target word, and each Letter
+/// corresponds to the letter in the same position in the target word.
+///
+/// Duplicates are handled as per the rules of Wordle.
+pub fn assess_pair(target: &str, guess: &str) -> Result<Vec<Letter>, WordleError> {
+    assess_normalized(&normalize(target, false), &normalize(guess, false))
+}
+
+/// `assess_normalized` is `assess_pair` for words that are already normalized, so
+/// callers that assess many guesses against one target normalize it only once.
+///
+/// Words are a handful of letters long, so letters are counted by scanning the words
+/// rather than with maps.
+fn assess_normalized(target_word: &str, word: &str) -> Result<Vec<Letter>, WordleError> {
+    if !word.chars().all(char::is_alphabetic) {
+        return Err(WordleError::NotLetters);
+    }
+
+    let target = target_word.chars().collect::<Vec<_>>();
+    let mut letters = Vec::with_capacity(target.len());
+    for (i, c) in word.chars().enumerate() {
+        letters.push(if target.get(i) == Some(&c) {
+            Letter::Correct(c)
+        } else if target.contains(&c) {
+            Letter::CorrectButWrongPosition(c)
+        } else {
+            Letter::Wrong(c)
+        });
+    }
+
+    if letters.len() != target.len() {
+        return Err(WordleError::WrongLength {
+            expected: target.len(),
+            got: letters.len(),
+        });
+    }
+
+    // Remove dups by replacing duplicated CorrectButWrongPosition letters with Wrong
+    // letters, from the left, while the guess has more of the letter than the target.
+    // https://wordfinder.yourdictionary.com/blog/can-letters-repeat-in-wordle-a-closer-look-at-the-rules/
+    for i in 0..letters.len() {
+        let Letter::CorrectButWrongPosition(c) = letters[i] else {
+            continue;
+        };
+
+        let played = letters
+            .iter()
+            .filter(
+                |l| matches!(l, Letter::Correct(x) | Letter::CorrectButWrongPosition(x) if *x == c),
+            )
+            .count();
+        if played > target.iter().filter(|t| **t == c).count() {
+            letters[i] = Letter::Wrong(c);
+        }
+    }
+
+    Ok(letters)
 }
 
 /// Wordle represents a single Worldle game.
@@ -60,110 +271,221 @@ pub struct Wordle {
 
     /// The words that the player has attempted so far.
     pub attempts: Vec<String>,
+
+    /// Whether guesses match letters regardless of accents.
+    #[serde(default)]
+    pub fold_accents: bool,
+
+    /// The number of attempts before the game is lost.
+    #[serde(default = "default_max_attempts")]
+    pub max_attempts: usize,
+
+    /// Whether the game is for practice, so it doesn't count toward scores.
+    #[serde(default)]
+    pub practice: bool,
+
+    /// Whether the player gave up, which loses the game.
+    #[serde(default)]
+    pub gave_up: bool,
 }
 
 impl Wordle {
     /// `new` creates a new Wordle game with the given target word.
-    pub fn new(target_word: String) -> anyhow::Result<Wordle> {
+    pub fn new(target_word: String) -> Result<Wordle, WordleError> {
         if target_word.len() < 3 {
-            anyhow::bail!("target_word must be at least 3 letters long")
+            return Err(WordleError::TooShort);
+        }
+
+        if target_word.chars().count() > MAX_WORD_LENGTH {
+            return Err(WordleError::TooLong);
+        }
+
+        if !target_word.chars().all(char::is_alphabetic) {
+            return Err(WordleError::NotLetters);
         }
 
         Ok(Wordle {
-            target_word: target_word.to_uppercase(),
+            target_word: normalize(&target_word, false),
             attempts: Vec::new(),
+            fold_accents: false,
+            max_attempts: MAX_ATTEMPTS,
+            practice: false,
+            gave_up: false,
         })
     }
 
+    /// `with_fold_accents` makes guesses match letters regardless of accents. The target
+    /// is still shown with its accents.
+    pub fn with_fold_accents(mut self, fold_accents: bool) -> Wordle {
+        self.fold_accents = fold_accents;
+        self
+    }
+
+    /// `with_max_attempts` sets the number of attempts before the game is lost.
+    pub fn with_max_attempts(mut self, max_attempts: usize) -> Wordle {
+        self.max_attempts = max_attempts;
+        self
+    }
+
+    /// `with_practice` makes the game a practice game, which doesn't count toward
+    /// scores.
+    pub fn with_practice(mut self, practice: bool) -> Wordle {
+        self.practice = practice;
+        self
+    }
+
+    /// `is_target` returns true if the word is the target word.
+    pub fn is_target(&self, word: &str) -> bool {
+        normalize(word, self.fold_accents) == normalize(&self.target_word, self.fold_accents)
+    }
+
+    /// `fits` returns true if the word has as many letters as the target word.
+    pub fn fits(&self, word: &str) -> bool {
+        normalize(word, self.fold_accents).chars().count()
+            == normalize(&self.target_word, self.fold_accents)
+                .chars()
+                .count()
+    }
+
     /// `game` returns a Game instance that can be rendered and presented to the player.
-    pub fn game(&self) -> anyhow::Result<Game> {
-        let state = if self.attempts.contains(&self.target_word) {
+    ///
+    /// Attempts are normalized before they're compared to the target, so a game
+    /// restored from an old save with a lowercase target is still won by an
+    /// all-correct guess.
+    pub fn game(&self) -> Result<Game, WordleError> {
+        let target = normalize(&self.target_word, self.fold_accents);
+        let attempts = self
+            .attempts
+            .iter()
+            .map(|a| assess_normalized(&target, &normalize(a, self.fold_accents)))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        // An attempt is the target exactly when all its letters are correct.
+        let state = if attempts
+            .iter()
+            .any(|a| a.iter().all(|l| matches!(l, Letter::Correct(_))))
+        {
             State::Won
-        } else if self.attempts.len() >= 6 {
+        } else if self.gave_up || attempts.len() >= self.max_attempts {
             State::Lost
         } else {
             State::Playing
         };
 
-        let attempts: Result<Vec<_>, _> = self.attempts.iter().map(|a| self.assess(a)).collect();
-
         Ok(Game {
             state,
-            attempts: attempts?,
+            attempts,
+            max_attempts: self.max_attempts,
         })
     }
 
+    /// `give_up` loses the game, unless it's already over.
+    pub fn give_up(&mut self) -> Result<Game, WordleError> {
+        if self.is_over() {
+            return Err(WordleError::GameOver);
+        }
+
+        self.gave_up = true;
+        self.game()
+    }
+
+    /// `is_over` returns true if the game has been won or lost. Unlike `game`, it
+    /// doesn't assess the attempts.
+    fn is_over(&self) -> bool {
+        if self.gave_up || self.attempts.len() >= self.max_attempts {
+            return true;
+        }
+
+        let target = normalize(&self.target_word, self.fold_accents);
+        self.attempts
+            .iter()
+            .any(|a| normalize(a, self.fold_accents) == target)
+    }
+
     // `assess` compares the given word to the target word, and returns a vector of positional
-    // Letter instances. The vector is the same length as the target word, and each Letter
-    // corresponds to the letter in the same position in the target word.
-    //
-    // Duplicates are handled as per the rules of Wordle.
-    pub fn assess(&self, word: impl Into<String>) -> anyhow::Result<Vec<Letter>> {
-        let word = word.into().to_uppercase();
-        if word.len() != self.target_word.len() {
-            anyhow::bail!("word must be {} characters long", self.target_word.len())
-        }
-
-        let mut letters = Vec::new();
-
-        // Keep track of the number of times each letter appears in the target word.
-        let target_letter_count = self.target_word.chars().fold(HashMap::new(), |mut acc, c| {
-            *acc.entry(c).or_insert(0) += 1;
-            acc
-        });
+    // Letter instances. See `assess_pair`.
+    pub fn assess(&self, word: impl Into<String>) -> Result<Vec<Letter>, WordleError> {
+        assess_normalized(
+            &normalize(&self.target_word, self.fold_accents),
+            &normalize(&word.into(), self.fold_accents),
+        )
+    }
 
-        // Keep track of the number of times each letter appears in the played word.
-        let mut dup_letter_count = HashMap::new();
-        for (i, c) in word.chars().enumerate() {
-            if self.target_word.contains(c) {
-                if self.target_word.chars().nth(i) == Some(c) {
-                    letters.push(Letter::Correct(c));
-                    *dup_letter_count.entry(c).or_insert(0) += 1;
-                } else {
-                    letters.push(Letter::CorrectButWrongPosition(c));
-                    *dup_letter_count.entry(c).or_insert(0) += 1;
+    /// `validate_hard_mode` checks that the word uses every hint so far: correct letters
+    /// stay in place, and letters in the wrong position are used again. If
+    /// `after_first_green` is set, hints are only enforced once a letter has been
+    /// placed correctly.
+    pub fn validate_hard_mode(
+        &self,
+        word: &str,
+        after_first_green: bool,
+    ) -> Result<(), WordleError> {
+        let word = normalize(word, self.fold_accents)
+            .chars()
+            .collect::<Vec<_>>();
+        let attempts = self
+            .attempts
+            .iter()
+            .map(|a| self.assess(a))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let has_green = attempts
+            .iter()
+            .flatten()
+            .any(|l| matches!(l, Letter::Correct(_)));
+        if after_first_green && !has_green {
+            return Ok(());
+        }
+
+        // Report misplaced correct letters first, as they're the stronger hint.
+        for attempt in &attempts {
+            for (i, letter) in attempt.iter().enumerate() {
+                if let Letter::Correct(c) = letter {
+                    if word.get(i) != Some(c) {
+                        return Err(WordleError::HardMode(format!(
+                            "letter {} must be {}",
+                            i + 1,
+                            c
+                        )));
+                    }
                 }
-            } else {
-                letters.push(Letter::Wrong(c));
             }
         }
 
-        // Remove dups by replacing duplicated CorrectButWrongPosition letters with Wrong letters.
-        // https://wordfinder.yourdictionary.com/blog/can-letters-repeat-in-wordle-a-closer-look-at-the-rules/
-        letters = letters
-            .iter()
-            .map(|l| match l {
-                Letter::Correct(c) => Letter::Correct(*c),
-                Letter::CorrectButWrongPosition(c) => {
-                    let letter_count = dup_letter_count.entry(*c).or_insert(0);
-                    if *letter_count > *target_letter_count.get(c).unwrap_or(&0) {
-                        *letter_count -= 1;
-                        Letter::Wrong(*c)
-                    } else {
-                        Letter::CorrectButWrongPosition(*c)
-                    }
+        for letter in attempts.iter().flatten() {
+            if let Letter::CorrectButWrongPosition(c) = letter {
+                if !word.contains(c) {
+                    return Err(WordleError::HardMode(format!(
+                        "the word must contain {}",
+                        c
+                    )));
                 }
-                Letter::Wrong(c) => Letter::Wrong(*c),
-            })
-            .collect();
+            }
+        }
 
-        Ok(letters)
+        Ok(())
     }
 
     /// `play_turn` plays a turn of the game, and returns a Game instance that can be rendered
     /// and presented to the player.
-    pub fn play_turn(&mut self, word: impl Into<String>) -> anyhow::Result<Game> {
-        let word = word.into().to_uppercase();
-        if word.len() != self.target_word.len() {
-            anyhow::bail!("word must be {} characters long", self.target_word.len())
+    pub fn play_turn(&mut self, word: impl Into<String>) -> Result<Game, WordleError> {
+        let word = normalize(&word.into(), false);
+        if !self.fits(&word) {
+            return Err(WordleError::WrongLength {
+                expected: self.target_word.chars().count(),
+                got: word.chars().count(),
+            });
+        }
+
+        if self.attempts.len() >= MAX_HISTORY {
+            return Err(WordleError::TooManyAttempts);
         }
 
-        let game = self.game()?;
-        if game.state != State::Playing {
-            anyhow::bail!("game is over")
+        if self.is_over() {
+            return Err(WordleError::GameOver);
         }
 
-        self.attempts.push(word.clone());
+        self.attempts.push(word);
         self.game()
     }
 }
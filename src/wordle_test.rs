@@ -1,3 +1,5 @@
+use std::collections::HashMap;
+
 use crate::wordle::*;
 
 #[test]
@@ -25,3 +27,276 @@ fn it_works() {
     let game = wordle.play_turn("hello").unwrap();
     assert_eq!(game.state, State::Won);
 }
+
+#[test]
+fn within_one_edit() {
+    assert!(crate::wordle::within_one_edit("HELLO", "HELLO"));
+    assert!(crate::wordle::within_one_edit("HELLO", "HELLS"));
+    assert!(crate::wordle::within_one_edit("HELLO", "HELO"));
+    assert!(crate::wordle::within_one_edit("HELO", "HELLO"));
+    assert!(!crate::wordle::within_one_edit("HELLO", "WORLD"));
+    assert!(!crate::wordle::within_one_edit("HELLO", "HEL"));
+}
+
+#[test]
+fn attempts_are_capped() {
+    // Games normally end long before the cap, so fill the history directly.
+    let mut wordle = Wordle::new("hello".into()).unwrap();
+    wordle.attempts = vec!["BELLY".to_string(); MAX_HISTORY];
+
+    let err = wordle.play_turn("hello").unwrap_err();
+    assert_eq!(err, WordleError::TooManyAttempts);
+    assert!(err.to_string().contains("limit of 100 attempts"));
+    assert_eq!(wordle.attempts.len(), MAX_HISTORY);
+}
+
+#[test]
+fn attempted_letters_in_order() {
+    let mut wordle = Wordle::new("hello".into()).unwrap();
+    wordle.play_turn("world").unwrap();
+    let game = wordle.play_turn("belly").unwrap();
+
+    assert_eq!(
+        game.attempted_letters_in_order(),
+        vec!['W', 'O', 'R', 'L', 'D', 'B', 'E', 'Y']
+    );
+    assert_eq!(
+        game.attempted_letters(),
+        vec!['B', 'D', 'E', 'L', 'O', 'R', 'W', 'Y']
+    );
+}
+
+#[test]
+fn wrong_length_error() {
+    let mut wordle = Wordle::new("hello".into()).unwrap();
+    match wordle.play_turn("hi") {
+        Err(WordleError::WrongLength { expected, got }) => {
+            assert_eq!(expected, 5);
+            assert_eq!(got, 2);
+        }
+        other => panic!("expected WrongLength, got {:?}", other.map(|g| g.state)),
+    }
+
+    wordle.play_turn("hello").unwrap();
+    assert_eq!(
+        wordle.play_turn("hello").unwrap_err(),
+        WordleError::GameOver
+    );
+    assert_eq!(Wordle::new("hi".into()).unwrap_err(), WordleError::TooShort);
+}
+
+#[test]
+fn long_targets_rejected() {
+    assert_eq!(
+        Wordle::new("incomprehensibilities".into()).unwrap_err(),
+        WordleError::TooLong
+    );
+    assert!(Wordle::new("a".repeat(MAX_WORD_LENGTH)).is_ok());
+}
+
+#[test]
+fn phrases_rejected() {
+    assert_eq!(
+        Wordle::new("ICE CREAM".into()).unwrap_err(),
+        WordleError::NotLetters
+    );
+
+    let wordle = Wordle::new("hello".into()).unwrap();
+    assert_eq!(wordle.assess("he-lo").unwrap_err(), WordleError::NotLetters);
+}
+
+/// The straightforward assessment `assess_pair` used to do, with maps of letter
+/// counts. It's kept as a reference for the allocation-free version.
+fn reference_assess(target: &str, guess: &str) -> Vec<Letter> {
+    let (target, guess) = (target.to_uppercase(), guess.to_uppercase());
+    let target_count = target.chars().fold(HashMap::new(), |mut acc, c| {
+        *acc.entry(c).or_insert(0) += 1;
+        acc
+    });
+
+    let mut played_count = HashMap::new();
+    let letters = guess
+        .chars()
+        .enumerate()
+        .map(|(i, c)| {
+            if !target.contains(c) {
+                return Letter::Wrong(c);
+            }
+            *played_count.entry(c).or_insert(0) += 1;
+            if target.chars().nth(i) == Some(c) {
+                Letter::Correct(c)
+            } else {
+                Letter::CorrectButWrongPosition(c)
+            }
+        })
+        .collect::<Vec<_>>();
+
+    letters
+        .into_iter()
+        .map(|l| match l {
+            Letter::CorrectButWrongPosition(c) => {
+                let count = played_count.get_mut(&c).unwrap();
+                if *count > target_count[&c] {
+                    *count -= 1;
+                    Letter::Wrong(c)
+                } else {
+                    l
+                }
+            }
+            l => l,
+        })
+        .collect()
+}
+
+/// Every pair of words, including ones with repeated letters, is assessed the same
+/// as by the reference.
+#[test]
+fn assess_pair_matches_reference() {
+    let words = [
+        "hello", "level", "llama", "bolle", "eerie", "belle", "lolly", "crane", "leapt", "hotel",
+        "sheep", "geese", "allee", "lllll", "ollie",
+    ];
+    for target in words {
+        for guess in words {
+            assert_eq!(
+                assess_pair(target, guess).unwrap(),
+                reference_assess(target, guess),
+                "{} against {}",
+                guess,
+                target
+            );
+        }
+    }
+}
+
+#[test]
+fn assess_pair_duplicates() {
+    use Letter::*;
+
+    // The same case as `it_works`, without a Wordle.
+    assert_eq!(
+        assess_pair("hello", "bolle").unwrap(),
+        vec![
+            Wrong('B'),
+            CorrectButWrongPosition('O'),
+            Correct('L'),
+            Correct('L'),
+            CorrectButWrongPosition('E')
+        ]
+    );
+
+    // A letter that's already correct isn't also reported elsewhere.
+    assert_eq!(
+        assess_pair("hello", "level").unwrap(),
+        vec![
+            CorrectButWrongPosition('L'),
+            Correct('E'),
+            Wrong('V'),
+            Wrong('E'),
+            CorrectButWrongPosition('L')
+        ]
+    );
+
+    // Both copies of a doubled letter are reported when the target has two.
+    assert_eq!(
+        assess_pair("hello", "llama").unwrap(),
+        vec![
+            CorrectButWrongPosition('L'),
+            CorrectButWrongPosition('L'),
+            Wrong('A'),
+            Wrong('M'),
+            Wrong('A')
+        ]
+    );
+
+    // Wordle::assess delegates to assess_pair.
+    let wordle = Wordle::new("hello".into()).unwrap();
+    assert_eq!(
+        wordle.assess("level").unwrap(),
+        assess_pair("HELLO", "LEVEL").unwrap()
+    );
+    assert_eq!(
+        assess_pair("hello", "hi").unwrap_err(),
+        WordleError::WrongLength {
+            expected: 5,
+            got: 2
+        }
+    );
+}
+
+/// Whenever an assessment is all correct, the game is won, whatever the case of the
+/// target and the guesses.
+#[test]
+fn all_correct_means_won() {
+    let words = [
+        "hello", "HELLO", "Hello", "crane", "llama", "abbey", "straße",
+    ];
+    for target in words {
+        for guess in words {
+            // Games restored from saves may hold targets that weren't normalized.
+            let mut wordle: Wordle = serde_json::from_value(serde_json::json!({
+                "target_word": target,
+                "attempts": [],
+            }))
+            .unwrap();
+
+            let Ok(game) = wordle.play_turn(guess) else {
+                continue;
+            };
+            let all_correct = game
+                .attempts
+                .last()
+                .unwrap()
+                .iter()
+                .all(|l| matches!(l, Letter::Correct(_)));
+            assert_eq!(
+                all_correct,
+                game.state == State::Won,
+                "target {} guess {}",
+                target,
+                guess
+            );
+        }
+    }
+}
+
+#[test]
+fn fold_accents_option() {
+    assert_eq!(fold_accents("CAFÉ"), "CAFE");
+    assert_eq!(fold_accents("cafe\u{301}"), "cafe");
+
+    // Without folding, accents matter, but not how they're encoded.
+    let mut wordle = Wordle::new("café".into()).unwrap();
+    assert_eq!(wordle.play_turn("cafe").unwrap().state, State::Playing);
+    assert_eq!(wordle.play_turn("cafe\u{301}").unwrap().state, State::Won);
+
+    let mut wordle = Wordle::new("café".into()).unwrap().with_fold_accents(true);
+    let game = wordle.play_turn("cafe").unwrap();
+    assert_eq!(game.state, State::Won);
+    assert_eq!(wordle.target_word, "CAFÉ");
+}
+
+#[test]
+fn hard_mode() {
+    let mut wordle = Wordle::new("hello".into()).unwrap();
+
+    // OCEAN finds a misplaced O and E, but nothing in the right spot.
+    wordle.play_turn("ocean").unwrap();
+    assert!(wordle.validate_hard_mode("train", true).is_ok());
+    assert_eq!(
+        wordle.validate_hard_mode("train", false),
+        Err(WordleError::HardMode("the word must contain O".into()))
+    );
+
+    // BELOW puts E and L in the right spot, so hints are now enforced.
+    wordle.play_turn("below").unwrap();
+    assert_eq!(
+        wordle.validate_hard_mode("train", true),
+        Err(WordleError::HardMode("letter 2 must be E".into()))
+    );
+    assert_eq!(
+        wordle.validate_hard_mode("xelxx", true),
+        Err(WordleError::HardMode("the word must contain O".into()))
+    );
+    assert!(wordle.validate_hard_mode("helot", true).is_ok());
+}
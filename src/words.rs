@@ -0,0 +1,208 @@
+/// Word list loading. Target and valid words live in plain text files with one
+/// word per line, or together in one combined file with each word marked.
+use std::collections::{HashMap, HashSet};
+
+use log::*;
+
+// parse_lines returns the lines of a word file. It filters out empty lines and
+// lines that start with a '#'. Files saved on Windows may start with a byte order
+// mark and end lines with CRLF, so both are stripped.
+fn parse_lines(contents: &str) -> Vec<String> {
+    contents
+        .strip_prefix('\u{FEFF}')
+        .unwrap_or(contents)
+        .lines()
+        .map(|s| s.trim_end_matches('\r').to_string())
+        .filter(|s| !s.starts_with("#"))
+        .filter(|s| !s.trim().is_empty())
+        .collect()
+}
+
+// read_lines reads a file and returns its lines, as per parse_lines.
+fn read_lines(path: impl AsRef<str>) -> Vec<String> {
+    parse_lines(&std::fs::read_to_string(path.as_ref()).unwrap_or_default())
+}
+
+/// parse_words returns the words of a word file read from `path`. Phrases and
+/// hyphenated words (e.g. "ICE CREAM") can't be played, so they're skipped with a
+/// warning.
+pub fn parse_words(contents: &str, path: &str) -> Vec<String> {
+    parse_lines(contents)
+        .into_iter()
+        .map(|s| s.trim().to_string())
+        .filter(|s| {
+            let is_word = is_word(s);
+            if !is_word {
+                warn!("Skipping {:?} in {}: words must be letters only", s, path);
+            }
+            is_word
+        })
+        .collect()
+}
+
+// is_word returns true if the string is a playable word, i.e. letters only.
+fn is_word(s: &str) -> bool {
+    s.chars().all(char::is_alphabetic)
+}
+
+/// parse_combined_words splits a combined word file read from `path` into target and
+/// valid-only words. Each line is a word and a marker: T for a target word, or V
+/// for a word that's only valid as a guess, e.g. "crane T". Lines are filtered as per
+/// parse_lines, and malformed lines are skipped with a warning.
+pub fn parse_combined_words(contents: &str, path: &str) -> (Vec<String>, Vec<String>) {
+    let mut targets = vec![];
+    let mut valid = vec![];
+    for line in parse_lines(contents) {
+        let mut fields = line.split_whitespace();
+        match (fields.next(), fields.next(), fields.next()) {
+            (Some(word), Some("T" | "t"), None) if is_word(word) => targets.push(word.into()),
+            (Some(word), Some("V" | "v"), None) if is_word(word) => valid.push(word.into()),
+            _ => warn!(
+                "Skipping {:?} in {}: expected a word followed by T or V",
+                line, path
+            ),
+        }
+    }
+
+    (targets, valid)
+}
+
+/// Reads a combined word file, as per parse_combined_words, without blocking the
+/// runtime. Returns the target and valid-only words.
+pub async fn load_combined_words(path: impl AsRef<str>) -> (Vec<String>, Vec<String>) {
+    let contents = tokio::fs::read_to_string(path.as_ref())
+        .await
+        .unwrap_or_default();
+    parse_combined_words(&contents, path.as_ref())
+}
+
+/// Reads a file containing one word per line, as per parse_words, without blocking
+/// the runtime.
+pub async fn load_words(path: impl AsRef<str>) -> Vec<String> {
+    let contents = tokio::fs::read_to_string(path.as_ref())
+        .await
+        .unwrap_or_default();
+    parse_words(&contents, path.as_ref())
+}
+
+/// Reads a word frequency file, with a word and its count on each line, e.g.
+/// "HELLO 1234". Words are uppercased. Malformed lines are skipped.
+pub fn read_frequencies(path: impl AsRef<str>) -> HashMap<String, u64> {
+    read_lines(path)
+        .iter()
+        .filter_map(|line| {
+            let (word, count) = line.split_once(char::is_whitespace)?;
+            Some((word.to_uppercase(), count.trim().parse().ok()?))
+        })
+        .collect()
+}
+
+/// Reads every valid-word file in `paths` concurrently and merges them into one set.
+/// The target words are always included, so every target is a valid guess.
+/// Valid-word lists can run to hundreds of thousands of lines, so they're read
+/// without blocking the runtime, which keeps the bot responsive during /reload.
+pub async fn load_valid_words(paths: &[String], target_words: &[String]) -> HashSet<String> {
+    let mut tasks = tokio::task::JoinSet::new();
+    for path in paths.iter().cloned() {
+        tasks.spawn(async move { (load_words(&path).await, path) });
+    }
+
+    let mut valid_words = HashSet::new();
+    while let Some(result) = tasks.join_next().await {
+        match result {
+            Ok((words, path)) => {
+                info!("Read {} valid words from {}", words.len(), path);
+                valid_words.extend(words);
+            }
+            Err(e) => error!("Error reading valid words: {}", e),
+        }
+    }
+
+    add_targets(&mut valid_words, target_words);
+    valid_words
+}
+
+/// Adds the target words to the valid words, so every target is a valid guess.
+pub fn add_targets(valid_words: &mut HashSet<String>, target_words: &[String]) {
+    target_words.iter().for_each(|w| {
+        valid_words.insert(w.to_ascii_lowercase());
+    });
+}
+
+/// The number of example words listed for each issue found by validate_word_lists.
+const MAX_EXAMPLES: usize = 10;
+
+/// Lists up to MAX_EXAMPLES of the words, and how many more there are.
+fn examples(words: &[String]) -> String {
+    let mut listed = words
+        .iter()
+        .take(MAX_EXAMPLES)
+        .cloned()
+        .collect::<Vec<_>>()
+        .join(", ");
+    if words.len() > MAX_EXAMPLES {
+        listed.push_str(&format!(" and {} more", words.len() - MAX_EXAMPLES));
+    }
+    listed
+}
+
+/// validate_word_lists checks the loaded word lists, and returns a report of their
+/// sizes and of any target words that can't be played as expected: repeated ones,
+/// ones a game won't accept, and ones missing from the valid words. `modes` are the
+/// game modes and their target words.
+pub fn validate_word_lists(
+    targets: &[String],
+    valid_words: &HashSet<String>,
+    modes: &[(String, Vec<String>)],
+) -> String {
+    let mut report = format!(
+        "Target words: {}\nValid words: {}\n",
+        targets.len(),
+        valid_words.len()
+    );
+    for (name, words) in modes {
+        report.push_str(&format!("Mode {} target words: {}\n", name, words.len()));
+    }
+
+    let all_targets = targets
+        .iter()
+        .chain(modes.iter().flat_map(|(_, words)| words.iter()))
+        .collect::<Vec<_>>();
+
+    let mut seen = HashSet::new();
+    let repeated = all_targets
+        .iter()
+        .filter(|w| !seen.insert(w.to_uppercase()))
+        .map(|w| w.to_string())
+        .collect::<Vec<_>>();
+    let unplayable = all_targets
+        .iter()
+        .filter_map(|w| {
+            crate::wordle::Wordle::new(w.to_string())
+                .err()
+                .map(|e| format!("{} ({})", w, e))
+        })
+        .collect::<Vec<_>>();
+    let invalid = all_targets
+        .iter()
+        .filter(|w| !valid_words.is_empty() && !valid_words.contains(&w.to_ascii_lowercase()))
+        .map(|w| w.to_string())
+        .collect::<Vec<_>>();
+
+    let issues = [
+        (repeated, "Repeated target words"),
+        (unplayable, "Target words a game won't accept"),
+        (invalid, "Target words missing from the valid words"),
+    ]
+    .into_iter()
+    .filter(|(words, _)| !words.is_empty())
+    .map(|(words, issue)| format!("{} ({}): {}\n", issue, words.len(), examples(&words)))
+    .collect::<String>();
+
+    if issues.is_empty() {
+        report.push_str("No issues found.\n");
+    } else {
+        report.push_str(&issues);
+    }
+    report
+}
@@ -0,0 +1,112 @@
+use std::collections::HashSet;
+
+use crate::words::*;
+
+#[tokio::test]
+async fn merges_valid_word_files() {
+    let dir = std::env::temp_dir().join("wordlebot-merges-valid-word-files");
+    std::fs::create_dir_all(&dir).unwrap();
+    let us = dir.join("us.txt");
+    let uk = dir.join("uk.txt");
+    std::fs::write(&us, "# US English\ncolor\nhumor\n").unwrap();
+    std::fs::write(&uk, "colour\n\nhumour\n").unwrap();
+
+    let valid_words = load_valid_words(
+        &[
+            us.to_string_lossy().to_string(),
+            uk.to_string_lossy().to_string(),
+        ],
+        &["TRAIN".to_string()],
+    )
+    .await;
+
+    assert_eq!(valid_words.len(), 5);
+    assert!(valid_words.contains("color"));
+    assert!(valid_words.contains("colour"));
+    assert!(valid_words.contains("humour"));
+    assert!(valid_words.contains("train"));
+}
+
+#[tokio::test]
+async fn skips_phrases() {
+    let dir = std::env::temp_dir().join("wordlebot-skips-phrases");
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("targets.txt");
+    std::fs::write(&path, "ICE CREAM\nhello\nx-ray\n crane \n").unwrap();
+
+    assert_eq!(
+        load_words(path.to_string_lossy()).await,
+        vec!["hello".to_string(), "crane".to_string()]
+    );
+}
+
+#[tokio::test]
+async fn strips_bom_and_crlf() {
+    let dir = std::env::temp_dir().join("wordlebot-strips-bom-and-crlf");
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("targets.txt");
+    std::fs::write(&path, "\u{FEFF}crane\r\nhello\r\n\r\nstone\r\n").unwrap();
+
+    assert_eq!(
+        load_words(path.to_string_lossy()).await,
+        vec!["crane", "hello", "stone"]
+    );
+}
+
+/// Loading the files concurrently gives the same words as reading them one by one.
+#[tokio::test]
+async fn async_loading_matches_sync() {
+    let dir = std::env::temp_dir().join("wordlebot-async-loading");
+    std::fs::create_dir_all(&dir).unwrap();
+    let paths = (0..4)
+        .map(|i| {
+            let path = dir.join(format!("valid-{}.txt", i));
+            let words = (0..1000)
+                .map(|n| format!("w{}{}\n", "abcdefghij".as_bytes()[i] as char, n))
+                .collect::<String>()
+                .replace(|c: char| c.is_ascii_digit(), "z");
+            std::fs::write(&path, format!("# list {}\nice cream\n{}", i, words)).unwrap();
+            path.to_string_lossy().to_string()
+        })
+        .collect::<Vec<_>>();
+    let targets = ["CRANE".to_string()];
+
+    let mut expected = std::collections::HashSet::new();
+    for path in &paths {
+        expected.extend(parse_words(&std::fs::read_to_string(path).unwrap(), path));
+    }
+    add_targets(&mut expected, &targets);
+
+    assert_eq!(load_valid_words(&paths, &targets).await, expected);
+}
+
+/// A combined file is split into target and valid-only words, skipping malformed
+/// lines.
+#[test]
+fn parses_combined_words() {
+    let contents = "# targets and guesses\ncrane T\nhello t\n\naahed V\nzymic v\nice cream T\nquack\nlemon X\n";
+    let (targets, valid) = parse_combined_words(contents, "combined.txt");
+    assert_eq!(targets, vec!["crane".to_string(), "hello".to_string()]);
+    assert_eq!(valid, vec!["aahed".to_string(), "zymic".to_string()]);
+}
+
+#[test]
+fn validates_word_lists() {
+    let valid = HashSet::from(["hello".to_string(), "crane".to_string()]);
+    let report = validate_word_lists(
+        &["HELLO".to_string(), "crane".to_string()],
+        &valid,
+        &[("7".to_string(), vec!["hello".to_string()])],
+    );
+    assert_eq!(
+        report,
+        "Target words: 2\nValid words: 2\nMode 7 target words: 1\nRepeated target words (1): hello\n"
+    );
+
+    let report = validate_word_lists(&["hi".to_string(), "crane".to_string()], &valid, &[]);
+    assert!(report.contains("Target words a game won't accept (1): hi ("));
+    assert!(report.contains("Target words missing from the valid words (1): hi\n"));
+
+    let report = validate_word_lists(&["crane".to_string()], &valid, &[]);
+    assert!(report.ends_with("No issues found.\n"));
+}
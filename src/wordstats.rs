@@ -0,0 +1,50 @@
+/// Word stats track how each target word fares across all players, so operators
+/// can spot words that are too hard or too easy. They're saved as `word-stats.json`
+/// in the save directory.
+use std::collections::HashMap;
+
+use anyhow::Context;
+use serde::{Deserialize, Serialize};
+
+/// WordStats is how often a target word was served, won and lost.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct WordStats {
+    pub serves: u32,
+    pub wins: u32,
+    pub losses: u32,
+
+    /// The guesses of all winning games, for the average.
+    pub winning_guesses: u32,
+}
+
+impl WordStats {
+    /// Returns the average number of guesses among winners, or `None` if nobody has
+    /// won yet.
+    pub fn average_winning_guesses(&self) -> Option<f32> {
+        (self.wins > 0).then(|| self.winning_guesses as f32 / self.wins as f32)
+    }
+}
+
+/// Loads the word stats from the save directory, keyed by uppercase target word.
+pub async fn load_word_stats(save_dir: &str) -> anyhow::Result<HashMap<String, WordStats>> {
+    let filename = format!("{}/word-stats.json", save_dir);
+    let contents = tokio::fs::read(&filename)
+        .await
+        .context(format!("Error reading file {}", filename))?;
+
+    serde_json::from_slice(&contents).context(format!("Error deserializing file {}", filename))
+}
+
+/// Saves the word stats to the save directory.
+pub async fn save_word_stats(
+    save_dir: &str,
+    stats: &HashMap<String, WordStats>,
+) -> anyhow::Result<()> {
+    let filename = format!("{}/word-stats.json", save_dir);
+    tokio::fs::write(
+        &filename,
+        serde_json::to_vec(stats).context("Error serializing word stats")?,
+    )
+    .await
+    .context(format!("Error writing file {}", filename))
+}